@@ -1,4 +1,5 @@
 pub mod common;
+pub mod values;
 
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -13,6 +14,206 @@ pub use common::{
 pub mod commandargs {
     use super::*;
     use crate::args::common::{ChartFileIn, ChartFileOut};
+    use crate::args::values::{
+        ChartFormat, ColorLimitArg, GaugeArg, PointArg, RectArg, ScaleArg, SizeArg, SymbolLimitArg,
+    };
+    use dklib::chart::{Connectivity, HAlign, OddPolicy, SymmetryMode, VAlign};
+
+    #[derive(Debug, StructOpt)]
+    pub struct CheckArgs {
+        #[structopt(long, help = "report runs of the same stitch longer than this")]
+        pub max_run: Option<u32>,
+
+        #[structopt(
+            long,
+            number_of_values = 1,
+            help = "SYMBOL=LIMIT; the longest run allowed for one symbol, overriding --max-run"
+        )]
+        pub limit: Vec<SymbolLimitArg>,
+
+        #[structopt(
+            long,
+            number_of_values = 1,
+            help = "COLOR=LIMIT; the longest run allowed for one color, overriding --limit"
+        )]
+        pub color_limit: Vec<ColorLimitArg>,
+
+        #[structopt(
+            long,
+            help = "check that a merged chart alternates front and back stitches of opposite colors"
+        )]
+        pub double_knit: bool,
+
+        #[structopt(
+            long,
+            parse(from_os_str),
+            help = "write a chart image with the problems outlined"
+        )]
+        pub image: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        pub infile: ChartFileIn,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct CleanArgs {
+        #[structopt(long, help = "remove shapes and fill holes with fewer than N stitches")]
+        pub despeckle: Option<u32>,
+
+        #[structopt(long, help = "remove shapes with fewer than N stitches")]
+        pub min_region: Option<u32>,
+
+        #[structopt(long, help = "fill holes with fewer than N stitches")]
+        pub min_hole: Option<u32>,
+
+        #[structopt(long, help = "replace each stitch with the majority of its neighbors")]
+        pub majority: bool,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ConvertArgs {
+        #[structopt(
+            long,
+            default_value = "knit",
+            help = "format to read, 'knit', 'json' or 'csv'"
+        )]
+        pub from: ChartFormat,
+
+        #[structopt(long, help = "format to write, 'knit', 'json' or 'csv'")]
+        pub to: ChartFormat,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct CropArgs {
+        #[structopt(
+            long,
+            allow_hyphen_values = true,
+            help = "ROW,COL,WIDTHxHEIGHT; negative ROW or COL count from the end"
+        )]
+        pub rect: RectArg,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct DiffArgs {
+        #[structopt(
+            long,
+            help = "list the differences instead of printing a marked-up chart"
+        )]
+        pub list: bool,
+
+        #[structopt(long, default_value = "#", help = "stitch used to mark differences")]
+        pub marker: char,
+
+        #[structopt(parse(from_os_str))]
+        pub old_chart: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        pub new_chart: PathBuf,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct DilateArgs {
+        #[structopt(
+            long,
+            default_value = "1",
+            help = "how many stitches to grow each shape"
+        )]
+        pub radius: u16,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ErodeArgs {
+        #[structopt(
+            long,
+            default_value = "1",
+            help = "how many stitches to shrink each shape"
+        )]
+        pub radius: u16,
+
+        #[structopt(
+            long,
+            default_value = ".",
+            help = "stitch left behind by eroded stitches"
+        )]
+        pub background: char,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ExportArgs {
+        #[structopt(long, help = "format to write, 'csv' or 'json'")]
+        pub to: ChartFormat,
+
+        #[structopt(
+            long,
+            parse(from_os_str),
+            help = "write CSV colors to this file instead of into the cells with the symbols"
+        )]
+        pub colors: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct FillArgs {
+        #[structopt(
+            long,
+            allow_hyphen_values = true,
+            help = "ROW,COL of a stitch in the region to fill; negative values count from the end"
+        )]
+        pub at: PointArg,
+
+        #[structopt(long, help = "stitch to fill the region with")]
+        pub stitch: char,
+
+        #[structopt(
+            long,
+            default_value = "4",
+            help = "4 to fill through edges only, or 8 to fill through corners too"
+        )]
+        pub connectivity: Connectivity,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct HaloArgs {
+        #[structopt(
+            long,
+            default_value = "1",
+            help = "width of the ring around each shape"
+        )]
+        pub radius: u16,
+
+        #[structopt(long, default_value = ".", help = "stitch used for the ring")]
+        pub halo: char,
+
+        #[structopt(
+            long,
+            default_value = "~",
+            help = "stitch used outside of the ring; use with 'stamp --transparent'"
+        )]
+        pub outside: char,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
 
     #[derive(Debug, StructOpt)]
     pub struct ImageConvertArgs {
@@ -22,6 +223,12 @@ pub mod commandargs {
         #[structopt(long, short, help = "width in stitches of the final pattern")]
         pub width: Option<u16>,
 
+        #[structopt(
+            long,
+            help = "remove shapes and fill holes with fewer than N stitches after converting"
+        )]
+        pub despeckle: Option<u32>,
+
         #[structopt(flatten)]
         pub outfile: ChartFileOut,
 
@@ -29,6 +236,47 @@ pub mod commandargs {
         pub image_name: PathBuf,
     }
 
+    #[derive(Debug, StructOpt)]
+    pub struct ImportArgs {
+        #[structopt(long, help = "format to read, 'csv' or 'json'")]
+        pub from: ChartFormat,
+
+        #[structopt(
+            long,
+            parse(from_os_str),
+            help = "CSV file with a color for each stitch, lined up with the symbols"
+        )]
+        pub colors: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct InfoArgs {
+        #[structopt(
+            long,
+            default_value = "4",
+            help = "4 to join stitches through edges only, or 8 to join through corners too"
+        )]
+        pub connectivity: Connectivity,
+
+        #[structopt(long, help = "list every region of identical stitches")]
+        pub regions: bool,
+
+        #[structopt(long, help = "only list regions with at most this many stitches")]
+        pub max_size: Option<u32>,
+
+        #[structopt(flatten)]
+        pub infile: ChartFileIn,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct InvertArgs {
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
     #[derive(Debug, StructOpt)]
     pub struct KnitchartArgs {
         #[structopt(flatten)]
@@ -56,6 +304,15 @@ pub mod commandargs {
         pub out_file_name: Option<PathBuf>,
     }
 
+    #[derive(Debug, StructOpt)]
+    pub struct OutlineArgs {
+        #[structopt(long, default_value = ".", help = "stitch used inside of the outlines")]
+        pub background: char,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
     #[derive(Debug, StructOpt)]
     pub struct PadArgs {
         #[structopt(flatten)]
@@ -67,7 +324,10 @@ pub mod commandargs {
 
     #[derive(Debug, StructOpt)]
     pub struct ReflectArgs {
-        #[structopt(long)]
+        #[structopt(
+            long,
+            help = "flip the reading direction too, e.g. for left-handed knitters"
+        )]
         pub right_to_left: bool,
 
         #[structopt(flatten)]
@@ -87,6 +347,27 @@ pub mod commandargs {
 
         #[structopt(long, short, default_value("1"))]
         pub vert: u8,
+
+        #[structopt(long, help = "fill exactly this width, clipping partial repeats")]
+        pub width: Option<u16>,
+
+        #[structopt(long, help = "fill exactly this height, clipping partial repeats")]
+        pub height: Option<u16>,
+
+        #[structopt(long, help = "center the repeats so both edges are clipped equally")]
+        pub center: bool,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ReplaceArgs {
+        #[structopt(long, help = "symbols to replace")]
+        pub from: String,
+
+        #[structopt(long, help = "replacement symbols, in the same order as --from")]
+        pub to: String,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
     }
 
     #[derive(Debug, StructOpt)]
@@ -95,6 +376,76 @@ pub mod commandargs {
         pub pipe: common::Pipeable,
     }
 
+    #[derive(Debug, StructOpt)]
+    pub struct RollArgs {
+        #[structopt(
+            long,
+            default_value = "0",
+            allow_hyphen_values = true,
+            help = "columns to move right (negative moves left)"
+        )]
+        pub dx: i32,
+
+        #[structopt(
+            long,
+            default_value = "0",
+            allow_hyphen_values = true,
+            help = "rows to move down (negative moves up)"
+        )]
+        pub dy: i32,
+
+        #[structopt(
+            long,
+            help = "drop stitches at the edges instead of wrapping them around"
+        )]
+        pub no_wrap: bool,
+
+        #[structopt(long, default_value = ".", help = "stitch used to fill with --no-wrap")]
+        pub background: char,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ScaleArgs {
+        #[structopt(
+            long,
+            required_unless = "to",
+            conflicts_with = "to",
+            help = "FACTOR or HFACTORxVFACTOR, e.g. '2', '0.5' or '2x3'"
+        )]
+        pub factor: Option<ScaleArg>,
+
+        #[structopt(long, help = "WIDTHxHEIGHT, WIDTHx, or xHEIGHT")]
+        pub to: Option<SizeArg>,
+
+        #[structopt(long, help = "shrink by sampling instead of by majority vote")]
+        pub nearest: bool,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct ScanArgs {
+        #[structopt(
+            long,
+            default_value = "*",
+            help = "stitch for cells with a symbol; symbols aren't recognized, so all of them become this"
+        )]
+        pub mark: char,
+
+        #[structopt(long, default_value = ".", help = "stitch for empty cells")]
+        pub blank: char,
+
+        #[structopt(flatten)]
+        pub outfile: ChartFileOut,
+
+        #[structopt(parse(from_os_str))]
+        pub image_name: PathBuf,
+    }
+
     #[derive(Debug, StructOpt)]
     pub struct SplitArgs {
         #[structopt(flatten)]
@@ -102,6 +453,51 @@ pub mod commandargs {
 
         #[structopt(long = "output_stem", short = "o", parse(from_os_str))]
         pub out_file_stem: Option<PathBuf>,
+
+        #[structopt(long, short, help = "number of panels to split the chart into")]
+        pub panels: Option<u16>,
+
+        #[structopt(
+            long,
+            conflicts_with = "panels",
+            help = "split before this column (or row)"
+        )]
+        pub at: Option<u16>,
+
+        #[structopt(long, help = "split into horizontal strips instead of vertical ones")]
+        pub rows: bool,
+
+        #[structopt(
+            long,
+            help = "with --panels, where uneven stitches go: 'left', 'right', 'error', or 'duplicate'"
+        )]
+        pub odd: Option<OddPolicy>,
+
+        #[structopt(long, help = "with --panels, extend each panel into the next one")]
+        pub overlap: Option<u16>,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct StackArgs {
+        #[structopt(
+            long,
+            default_value = "left",
+            help = "one of 'left', 'center', or 'right'"
+        )]
+        pub align: HAlign,
+
+        #[structopt(
+            long,
+            default_value = ".",
+            help = "stitch used to fill narrower charts"
+        )]
+        pub background: char,
+
+        #[structopt(flatten)]
+        pub outfile: ChartFileOut,
+
+        #[structopt(parse(from_os_str), required = true)]
+        pub chart_files: Vec<PathBuf>,
     }
 
     #[derive(Debug, StructOpt)]
@@ -112,6 +508,12 @@ pub mod commandargs {
         #[structopt(short = "v", default_value = "0")]
         pub v_offset: u8,
 
+        #[structopt(
+            long,
+            help = "stitch in the stamp that leaves the chart underneath it alone"
+        )]
+        pub transparent: Option<char>,
+
         #[structopt(flatten)]
         pub outfile: ChartFileOut,
 
@@ -122,10 +524,86 @@ pub mod commandargs {
         pub stamp_file: PathBuf,
     }
 
+    #[derive(Debug, StructOpt)]
+    pub struct SymmetrizeArgs {
+        #[structopt(
+            long,
+            default_value = "quad",
+            help = "one of 'mirror', 'quad', 'rotate2', or 'rotate4'"
+        )]
+        pub mode: SymmetryMode,
+
+        #[structopt(long, help = "share the center row/column instead of duplicating it")]
+        pub shared_center: bool,
+
+        #[structopt(flatten)]
+        pub pipe: common::Pipeable,
+    }
+
     #[derive(Debug, StructOpt)]
     pub struct TrimArgs {
         #[structopt(flatten)]
         pub pipe: common::Pipeable,
+
+        #[structopt(long, conflicts_with_all = &["cols", "only"], help = "trim only the top and bottom")]
+        pub rows: bool,
+
+        #[structopt(
+            long,
+            conflicts_with = "only",
+            help = "trim only the left and right sides"
+        )]
+        pub cols: bool,
+
+        #[structopt(
+            long,
+            possible_values = &["top", "bottom", "left", "right"],
+            help = "trim only these sides"
+        )]
+        pub only: Vec<String>,
+
+        #[structopt(long, default_value = "0", help = "number of empty stitches to keep")]
+        pub margin: u16,
+
+        #[structopt(long, help = "output an empty chart instead of failing on one")]
+        pub allow_empty: bool,
+    }
+
+    #[derive(Debug, StructOpt)]
+    pub struct YarnArgs {
+        #[structopt(
+            long,
+            required_unless = "swatch",
+            conflicts_with = "swatch",
+            help = "yards of yarn used by a single stitch"
+        )]
+        pub yards_per_stitch: Option<f64>,
+
+        #[structopt(
+            long,
+            requires = "swatch-yards",
+            help = "size of a double-knit swatch, in STITCHESxROWS, to calibrate the yardage"
+        )]
+        pub swatch: Option<GaugeArg>,
+
+        #[structopt(long, help = "yards of both colors used to knit the swatch")]
+        pub swatch_yards: Option<f64>,
+
+        #[structopt(long, help = "STITCHESxROWS per 4 inches, to report the finished size")]
+        pub gauge: Option<GaugeArg>,
+
+        #[structopt(long, default_value = "10", help = "extra yarn to allow, in percent")]
+        pub waste: f64,
+
+        #[structopt(flatten)]
+        pub infile: ChartFileIn,
+
+        #[structopt(
+            long,
+            parse(from_os_str),
+            help = "chart for the back of the fabric; the front is reused if not given"
+        )]
+        pub back: Option<PathBuf>,
     }
 
     #[derive(Debug, StructOpt)]
@@ -133,7 +611,23 @@ pub mod commandargs {
         #[structopt(short, long)]
         pub out_file_name: Option<PathBuf>,
 
-        pub left_file_name: PathBuf,
-        pub right_file_name: PathBuf,
+        #[structopt(long, default_value = "0", help = "number of columns between charts")]
+        pub gutter: u16,
+
+        #[structopt(long, default_value = ".", help = "stitch used to fill the gutter")]
+        pub gutter_stitch: char,
+
+        #[structopt(
+            long,
+            default_value = "top",
+            help = "one of 'top', 'center', or 'bottom'"
+        )]
+        pub align: VAlign,
+
+        #[structopt(long, default_value = ".", help = "stitch used to fill shorter charts")]
+        pub background: char,
+
+        #[structopt(parse(from_os_str), required = true)]
+        pub chart_files: Vec<PathBuf>,
     }
 }
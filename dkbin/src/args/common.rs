@@ -1,7 +1,8 @@
+use crate::args::values::ChartFormat;
 use anyhow::Error;
-use dklib::chart::Chart;
+use dklib::chart::{Chart, ReadOptions, ReadWarning};
 use fehler::throws;
-use std::io::{BufReader, Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
@@ -40,6 +41,26 @@ pub struct Pipeable {
     pub outfile: ChartFileOut,
 }
 
+/// Read a chart with `options`, printing any warnings to stderr.
+#[throws(dklib::Error)]
+pub fn read_chart(rdr: &mut impl BufRead, file: Option<&Path>, options: &ReadOptions) -> Chart {
+    let (chart, warnings) = Chart::read_with(rdr, file, options)?;
+    print_warnings(warnings);
+    chart
+}
+
+fn print_warnings(warnings: Vec<ReadWarning>) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+#[throws(dklib::Error)]
+pub fn read_chart_file(path: impl AsRef<Path>, options: &ReadOptions) -> Chart {
+    let file = std::fs::File::open(path.as_ref())?;
+    read_chart(&mut BufReader::new(file), Some(path.as_ref()), options)?
+}
+
 #[throws]
 fn pipe_in<P>(path: &Option<P>) -> Box<dyn Read>
 where
@@ -78,17 +99,82 @@ pub fn pipe_command(
 }
 
 #[throws]
-pub fn chart_in(infile: &ChartFileIn) -> Chart {
-    chart_path_in(&infile.chart_file_in)?
+pub fn chart_in(infile: &ChartFileIn, options: &ReadOptions) -> Chart {
+    chart_path_in(&infile.chart_file_in, options)?
 }
 
 #[throws]
-pub fn chart_path_in<P>(in_path: &Option<P>) -> Chart
+pub fn chart_path_in<P>(in_path: &Option<P>, options: &ReadOptions) -> Chart
 where
     P: AsRef<Path>,
 {
     let rdr = pipe_in(in_path)?;
-    Chart::read(&mut BufReader::new(rdr))?
+    let file = in_path.as_ref().map(|p| p.as_ref());
+    read_chart(&mut BufReader::new(rdr), file, options)?
+}
+
+/// Read a chart in `format`. CSV charts may take their colors from a separate `colors` file.
+#[throws]
+pub fn formatted_chart_in(
+    format: ChartFormat,
+    infile: &ChartFileIn,
+    colors: Option<&Path>,
+    options: &ReadOptions,
+) -> Chart {
+    match format {
+        ChartFormat::Csv => csv_chart_in(infile, colors, options)?,
+        ChartFormat::Json => json_chart_in(infile)?,
+        ChartFormat::Knit => chart_in(infile, options)?,
+    }
+}
+
+/// Write a chart in `format`. CSV charts write their colors to `colors` if it is given, or
+/// into the cells with the symbols if not.
+#[throws]
+pub fn formatted_chart_out(
+    format: ChartFormat,
+    outfile: &ChartFileOut,
+    chart: &Chart,
+    colors: Option<&Path>,
+) {
+    match format {
+        ChartFormat::Csv => csv_chart_out(outfile, chart, colors)?,
+        ChartFormat::Json => json_chart_out(outfile, chart)?,
+        ChartFormat::Knit => chart_out(outfile, chart)?,
+    }
+}
+
+#[throws]
+fn csv_chart_in(infile: &ChartFileIn, colors: Option<&Path>, options: &ReadOptions) -> Chart {
+    let rdr = pipe_in(&infile.chart_file_in)?;
+    let (mut chart, warnings) = Chart::read_csv(rdr, infile.chart_file_in.as_deref(), options)?;
+    print_warnings(warnings);
+    if let Some(colors) = colors {
+        chart.read_csv_colors(std::fs::File::open(colors)?, Some(colors))?;
+    }
+    chart
+}
+
+#[throws]
+fn csv_chart_out(outfile: &ChartFileOut, chart: &Chart, colors: Option<&Path>) {
+    let mut wtr = pipe_out(&outfile.chart_file_out)?;
+    chart.write_csv(&mut wtr, colors.is_none())?;
+    if let Some(colors) = colors {
+        chart.write_csv_colors(&mut std::fs::File::create(colors)?)?;
+    }
+}
+
+#[throws]
+fn json_chart_in(infile: &ChartFileIn) -> Chart {
+    let rdr = pipe_in(&infile.chart_file_in)?;
+    serde_json::from_reader(BufReader::new(rdr))?
+}
+
+#[throws]
+fn json_chart_out(outfile: &ChartFileOut, chart: &Chart) {
+    let mut wtr = pipe_out(&outfile.chart_file_out)?;
+    serde_json::to_writer_pretty(&mut wtr, chart)?;
+    writeln!(wtr)?;
 }
 
 #[throws]
@@ -108,13 +194,19 @@ where
 
 // TODO: get these dklib::Result references out of here.
 #[throws]
-pub fn pipe_chart(pipe: Pipeable, cmd: impl FnOnce(&Chart) -> dklib::Result<Chart>) {
+pub fn pipe_chart(
+    pipe: Pipeable,
+    options: &ReadOptions,
+    cmd: impl FnOnce(&Chart) -> dklib::Result<Chart>,
+) {
+    let in_path = pipe.infile.chart_file_in.clone();
     pipe_command(
         pipe.infile.chart_file_in,
         pipe.outfile.chart_file_out,
         |rdr, wtr| {
-            let chart = Chart::read(&mut BufReader::new(rdr))?;
-            let out_chart = cmd(&chart)?;
+            let chart = read_chart(&mut BufReader::new(rdr), in_path.as_deref(), options)?;
+            let mut out_chart = cmd(&chart)?;
+            out_chart.inherit_metadata(&chart);
             out_chart.write(wtr)
         },
     )?;
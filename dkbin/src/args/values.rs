@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Error};
+use css_color_parser::Color;
+use fehler::throws;
+use std::str::FromStr;
+
+/// A file format for charts, given on the command line as `knit`, `json` or `csv`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChartFormat {
+    Csv,
+    Json,
+    Knit,
+}
+
+impl FromStr for ChartFormat {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> ChartFormat {
+        match s {
+            "csv" => ChartFormat::Csv,
+            "json" => ChartFormat::Json,
+            "knit" => ChartFormat::Knit,
+            _ => Err(anyhow!(
+                "Chart format should be 'knit', 'json' or 'csv': {}",
+                s
+            ))?,
+        }
+    }
+}
+
+/// A knitting gauge given on the command line as `STITCHESxROWS`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GaugeArg {
+    pub stitches: f64,
+    pub rows: f64,
+}
+
+impl FromStr for GaugeArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> GaugeArg {
+        let parts: Vec<&str> = s.split('x').collect();
+        if parts.len() != 2 {
+            Err(anyhow!("Gauge should look like STITCHESxROWS: {}", s))?;
+        }
+        let gauge = GaugeArg {
+            stitches: parts[0].trim().parse()?,
+            rows: parts[1].trim().parse()?,
+        };
+        if !(gauge.stitches > 0.0 && gauge.rows > 0.0) {
+            Err(anyhow!("Gauge must be positive: {}", s))?;
+        }
+        gauge
+    }
+}
+
+/// A rectangle given on the command line as `ROW,COL,WIDTHxHEIGHT`.
+/// `ROW` and `COL` may be negative, in which case they count back from the end of the chart.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RectArg {
+    pub row: i32,
+    pub col: i32,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl FromStr for RectArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> RectArg {
+        let parts: Vec<&str> = s.split(',').collect();
+        if parts.len() != 3 {
+            Err(anyhow!(
+                "Rectangle should look like ROW,COL,WIDTHxHEIGHT: {}",
+                s
+            ))?;
+        }
+        let size: Vec<&str> = parts[2].split('x').collect();
+        if size.len() != 2 {
+            Err(anyhow!("Size should look like WIDTHxHEIGHT: {}", parts[2]))?;
+        }
+        RectArg {
+            row: parts[0].trim().parse()?,
+            col: parts[1].trim().parse()?,
+            width: size[0].trim().parse()?,
+            height: size[1].trim().parse()?,
+        }
+    }
+}
+
+/// A single stitch position given on the command line as `ROW,COL`.
+/// `ROW` and `COL` may be negative, in which case they count back from the end of the chart.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PointArg {
+    pub row: i32,
+    pub col: i32,
+}
+
+impl FromStr for PointArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> PointArg {
+        let parts: Vec<&str> = s.split(',').collect();
+        if parts.len() != 2 {
+            Err(anyhow!("Position should look like ROW,COL: {}", s))?;
+        }
+        PointArg {
+            row: parts[0].trim().parse()?,
+            col: parts[1].trim().parse()?,
+        }
+    }
+}
+
+/// A limit for a single symbol given on the command line as `SYMBOL=LIMIT`, e.g. `*=5`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SymbolLimitArg {
+    pub symbol: char,
+    pub limit: u32,
+}
+
+impl FromStr for SymbolLimitArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> SymbolLimitArg {
+        let mut chars = s.chars();
+        let symbol = chars.next();
+        let equals = chars.next();
+        match (symbol, equals) {
+            (Some(symbol), Some('=')) => SymbolLimitArg {
+                symbol,
+                limit: chars.as_str().trim().parse()?,
+            },
+            _ => Err(anyhow!("Limit should look like SYMBOL=LIMIT: {}", s))?,
+        }
+    }
+}
+
+/// The longest run allowed for one color, given on the command line as `COLOR=LIMIT`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorLimitArg {
+    pub color: Color,
+    pub limit: u32,
+}
+
+impl FromStr for ColorLimitArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> ColorLimitArg {
+        let mut parts = s.rsplitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(limit), Some(color)) => ColorLimitArg {
+                color: Color::from_str(color.trim())
+                    .map_err(|_| anyhow!("'{}' is not a valid color", color))?,
+                limit: limit.trim().parse()?,
+            },
+            _ => Err(anyhow!("Color limit should look like COLOR=LIMIT: {}", s))?,
+        }
+    }
+}
+
+/// A chart size given on the command line as `WIDTHxHEIGHT`.
+/// Either dimension may be left out (`40x` or `x30`), in which case it should be computed
+/// from the other.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeArg {
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+}
+
+impl FromStr for SizeArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> SizeArg {
+        let parts: Vec<&str> = s.split('x').collect();
+        if parts.len() != 2 {
+            Err(anyhow!(
+                "Size should look like WIDTHxHEIGHT, WIDTHx, or xHEIGHT: {}",
+                s
+            ))?;
+        }
+        let parse = |part: &str| -> Result<Option<u16>, Error> {
+            let part = part.trim();
+            if part.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(part.parse()?))
+            }
+        };
+        let size = SizeArg {
+            width: parse(parts[0])?,
+            height: parse(parts[1])?,
+        };
+        if size.width.is_none() && size.height.is_none() {
+            Err(anyhow!("Size needs at least a width or a height: {}", s))?;
+        }
+        size
+    }
+}
+
+/// A scale factor given on the command line as either `FACTOR` or `HFACTORxVFACTOR`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaleArg {
+    pub h: f64,
+    pub v: f64,
+}
+
+impl FromStr for ScaleArg {
+    type Err = Error;
+
+    #[throws]
+    fn from_str(s: &str) -> ScaleArg {
+        let parts: Vec<&str> = s.split('x').collect();
+        let (h, v): (f64, f64) = match parts.len() {
+            1 => (parts[0].trim().parse()?, parts[0].trim().parse()?),
+            2 => (parts[0].trim().parse()?, parts[1].trim().parse()?),
+            _ => Err(anyhow!(
+                "Scale should look like FACTOR or HFACTORxVFACTOR: {}",
+                s
+            ))?,
+        };
+        if !(h > 0.0 && v > 0.0) {
+            Err(anyhow!("Scale factors must be positive: {}", s))?;
+        }
+        ScaleArg { h, v }
+    }
+}
+
+/// Converts a possibly negative `index` into an index into something `size` long.
+/// Negative indices count back from the end, so that -1 is the last element.
+#[throws]
+pub fn resolve_index(index: i32, size: u32) -> u32 {
+    let resolved = if index < 0 {
+        i64::from(size) + i64::from(index)
+    } else {
+        i64::from(index)
+    };
+    if resolved < 0 || resolved >= i64::from(size) {
+        Err(anyhow!("Index {} is out of range for size {}", index, size))?;
+    }
+    resolved as u32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[throws]
+    fn test_chart_format() {
+        assert_eq!("json".parse::<ChartFormat>()?, ChartFormat::Json);
+        assert_eq!("knit".parse::<ChartFormat>()?, ChartFormat::Knit);
+        assert_eq!("csv".parse::<ChartFormat>()?, ChartFormat::Csv);
+        assert!("xls".parse::<ChartFormat>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_gauge_arg() {
+        assert_eq!(
+            "20x28".parse::<GaugeArg>()?,
+            GaugeArg {
+                stitches: 20.0,
+                rows: 28.0
+            }
+        );
+        assert_eq!("18.5x24".parse::<GaugeArg>()?.stitches, 18.5);
+        assert!("20".parse::<GaugeArg>().is_err());
+        assert!("0x28".parse::<GaugeArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_point_arg() {
+        assert_eq!("3,-1".parse::<PointArg>()?, PointArg { row: 3, col: -1 });
+        assert!("3".parse::<PointArg>().is_err());
+        assert!("3,4,5".parse::<PointArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_symbol_limit_arg() {
+        assert_eq!(
+            "*=5".parse::<SymbolLimitArg>()?,
+            SymbolLimitArg {
+                symbol: '*',
+                limit: 5
+            }
+        );
+        assert_eq!("==12".parse::<SymbolLimitArg>()?.symbol, '=');
+        assert!("*5".parse::<SymbolLimitArg>().is_err());
+        assert!("*=".parse::<SymbolLimitArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_color_limit_arg() {
+        let limit = "#daa520=6".parse::<ColorLimitArg>()?;
+        assert_eq!(limit.color, Color::from_str("goldenrod").unwrap());
+        assert_eq!(limit.limit, 6);
+        assert_eq!("lightblue = 3".parse::<ColorLimitArg>()?.limit, 3);
+        assert!("lightblue".parse::<ColorLimitArg>().is_err());
+        assert!("nope=3".parse::<ColorLimitArg>().is_err());
+        assert!("red=".parse::<ColorLimitArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_rect_arg() {
+        assert_eq!(
+            "10,5,20x30".parse::<RectArg>()?,
+            RectArg {
+                row: 10,
+                col: 5,
+                width: 20,
+                height: 30
+            }
+        );
+        assert_eq!("-3,-4,2x1".parse::<RectArg>()?.row, -3);
+
+        assert!("10,5".parse::<RectArg>().is_err());
+        assert!("10,5,20".parse::<RectArg>().is_err());
+        assert!("10,5,-2x3".parse::<RectArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_size_arg() {
+        assert_eq!(
+            "40x30".parse::<SizeArg>()?,
+            SizeArg {
+                width: Some(40),
+                height: Some(30)
+            }
+        );
+        assert_eq!(
+            "40x".parse::<SizeArg>()?,
+            SizeArg {
+                width: Some(40),
+                height: None
+            }
+        );
+        assert_eq!(
+            "x30".parse::<SizeArg>()?,
+            SizeArg {
+                width: None,
+                height: Some(30)
+            }
+        );
+        assert!("x".parse::<SizeArg>().is_err());
+        assert!("40".parse::<SizeArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_scale_arg() {
+        assert_eq!("2".parse::<ScaleArg>()?, ScaleArg { h: 2.0, v: 2.0 });
+        assert_eq!("0.5x3".parse::<ScaleArg>()?, ScaleArg { h: 0.5, v: 3.0 });
+        assert!("0".parse::<ScaleArg>().is_err());
+        assert!("1x2x3".parse::<ScaleArg>().is_err());
+    }
+
+    #[test]
+    #[throws]
+    fn test_resolve_index() {
+        assert_eq!(resolve_index(3, 10)?, 3);
+        assert_eq!(resolve_index(-1, 10)?, 9);
+        assert_eq!(resolve_index(-10, 10)?, 0);
+        assert!(resolve_index(-11, 10).is_err());
+        assert!(resolve_index(10, 10).is_err());
+    }
+}
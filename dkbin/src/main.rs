@@ -8,31 +8,69 @@ mod subcommands;
 /// A macro to avoid repetitive code to dispatch for all of the subcommands.
 /// It will complain at compile time if any subcommands are not included.
 macro_rules! dispatch {
-    ($($command:ident --> $proc:ident),*) => {
-        let subcommand = subcommands::SubCommands::from_args();
-        match subcommand {
+    ($subcommand:expr, $read:expr; $($command:ident --> $proc:ident),*) => {
+        match $subcommand {
             $(
-            subcommands::SubCommands::$command { args } => { subcommands::$proc(args)?; }
+            subcommands::SubCommands::$command { args } => { subcommands::$proc(args, $read)?; }
             )*
         }
     }
 }
 
-#[throws]
 fn main() {
+    if let Err(err) = run() {
+        if let Some(found) = err.downcast_ref::<subcommands::ProblemsFound>() {
+            eprintln!("{}", found);
+            std::process::exit(1);
+        }
+        eprintln!("Error: {:#}", err);
+        // Show the offending part of the file for parse errors.
+        if let Some(dklib::Error::Parse { location, .. }) = err.downcast_ref() {
+            eprintln!("{}", location.snippet());
+        }
+        std::process::exit(1);
+    }
+}
+
+#[throws]
+fn run() {
+    let dk = subcommands::Dk::from_args();
+    let read = dk.read_options();
+
     // Dispatch to all of the subcommands.
-    dispatch!(
+    dispatch!(dk.command, &read;
+        Check        --> check,
+        Clean        --> clean,
+        Convert      --> convert,
+        Crop         --> crop,
+        Diff         --> diff,
+        Dilate       --> dilate,
+        Erode        --> erode,
+        Export       --> export,
+        Fill         --> fill,
+        Halo         --> halo,
         ImageConvert --> image_convert,
+        Import       --> import,
+        Info         --> info,
+        Invert       --> invert,
         Knitchart    --> knitchart,
         Left         --> left,
         Merge        --> merge,
+        Outline      --> outline,
         Pad          --> pad,
         Reflect      --> reflect,
         Repeat       --> repeat,
+        Replace      --> replace,
         Right        --> right,
+        Roll         --> roll,
+        Scale        --> scale,
+        Scan         --> scan,
         Split        --> split,
+        Stack        --> stack,
         Stamp        --> stamp,
+        Symmetrize   --> symmetrize,
         Trim         --> trim,
+        Yarn         --> yarn,
         Zip          --> zip
     );
 }
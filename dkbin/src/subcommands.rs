@@ -1,13 +1,39 @@
 use crate::args::{
     chart_path_in, chart_path_out, commandargs,
-    common::{chart_in, chart_out, pipe_chart},
+    common::{
+        chart_in, chart_out, formatted_chart_in, formatted_chart_out, pipe_chart, read_chart_file,
+    },
+    values::{resolve_index, ChartFormat},
 };
 use anyhow::{anyhow, Error};
-use dklib::{chart::Chart, the_thing};
+use dklib::{
+    chart::{
+        Chart, CleanOptions, Connectivity, OddPolicy, PairProblem, ReadMode, ReadOptions,
+        RunLimits, ScanOptions, Stitch, TrimOptions, ZipOptions,
+    },
+    the_thing, the_thing_highlighted,
+};
 use fehler::throws;
-use std::path::{Path, PathBuf};
+use std::{
+    convert::TryInto,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
 use structopt::StructOpt;
 
+/// Returned by subcommands like `diff` that worked, but found something to report.
+/// The program prints the message without calling it an error, and exits with 1.
+#[derive(Debug)]
+pub struct ProblemsFound(pub String);
+
+impl Display for ProblemsFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ProblemsFound {}
+
 /// Makes a pathbuf from `path` but with the `.knit` extension.
 /// If `suffix` is provided, then append it to the file stem also.
 #[throws]
@@ -27,12 +53,104 @@ pub fn make_knit_pathbuf(path: impl AsRef<Path>, suffix: Option<&str>) -> PathBu
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "dk", about = "A tool for making double-knitting patterns.")]
+pub struct Dk {
+    /// Fail on charts whose rows are not all the same length, instead of padding them.
+    #[structopt(long, global = true)]
+    pub strict: bool,
+
+    /// Pad charts whose rows are not all the same length, but print a warning for each row.
+    #[structopt(long, global = true, conflicts_with = "strict")]
+    pub warn: bool,
+
+    #[structopt(subcommand)]
+    pub command: SubCommands,
+}
+
+impl Dk {
+    pub fn read_options(&self) -> ReadOptions {
+        let mode = if self.strict {
+            ReadMode::Strict
+        } else if self.warn {
+            ReadMode::Warn
+        } else {
+            ReadMode::Lenient
+        };
+        ReadOptions { mode }
+    }
+}
+
+#[derive(Debug, StructOpt)]
 pub enum SubCommands {
+    /// Check a chart for long runs or broken double-knitting pairs. Exits with 1 on problems.
+    Check {
+        #[structopt(flatten)]
+        args: commandargs::CheckArgs,
+    },
+    /// Remove stray stitches and small holes, e.g. after converting an image.
+    Clean {
+        #[structopt(flatten)]
+        args: commandargs::CleanArgs,
+    },
+    /// Convert a chart between the .knit, JSON and CSV formats.
+    Convert {
+        #[structopt(flatten)]
+        args: commandargs::ConvertArgs,
+    },
+    /// Cut a rectangle out of a chart.
+    Crop {
+        #[structopt(flatten)]
+        args: commandargs::CropArgs,
+    },
+    /// Compare two charts stitch by stitch. Exits with status 1 if they differ.
+    Diff {
+        #[structopt(flatten)]
+        args: commandargs::DiffArgs,
+    },
+    /// Thicken the shapes in a chart.
+    Dilate {
+        #[structopt(flatten)]
+        args: commandargs::DilateArgs,
+    },
+    /// Thin the shapes in a chart.
+    Erode {
+        #[structopt(flatten)]
+        args: commandargs::ErodeArgs,
+    },
+    /// Export a .knit chart as CSV or JSON.
+    Export {
+        #[structopt(flatten)]
+        args: commandargs::ExportArgs,
+    },
+    /// Fill a region of identical stitches with a new stitch.
+    Fill {
+        #[structopt(flatten)]
+        args: commandargs::FillArgs,
+    },
+    /// Surround the shapes in a chart with a ring of background, ready for stamping.
+    Halo {
+        #[structopt(flatten)]
+        args: commandargs::HaloArgs,
+    },
     /// Convert an image to a knit chart based on color values.
     ImageConvert {
         #[structopt(flatten)]
         args: commandargs::ImageConvertArgs,
     },
+    /// Import a chart from CSV or JSON as a .knit chart.
+    Import {
+        #[structopt(flatten)]
+        args: commandargs::ImportArgs,
+    },
+    /// Print statistics about a chart, such as stitch counts, runs, regions, and symmetry.
+    Info {
+        #[structopt(flatten)]
+        args: commandargs::InfoArgs,
+    },
+    /// Swap knits and purls, or the two colors of a colored chart.
+    Invert {
+        #[structopt(flatten)]
+        args: commandargs::InvertArgs,
+    },
     /// Outputs an chart image for a chart.
     Knitchart {
         #[structopt(flatten)]
@@ -48,41 +166,83 @@ pub enum SubCommands {
         #[structopt(flatten)]
         args: commandargs::MergeArgs,
     },
+    /// Keep only the edges of the shapes in a chart.
+    Outline {
+        #[structopt(flatten)]
+        args: commandargs::OutlineArgs,
+    },
     /// Adds one knit around the entire chart.
     Pad {
         #[structopt(flatten)]
         args: commandargs::PadArgs,
     },
-    /// Generate the mirror image of a chart.
+    /// Generate the mirror image of a chart, or with --right-to-left, the chart for knitting
+    /// in the other direction.
     Reflect {
         #[structopt(flatten)]
         args: commandargs::ReflectArgs,
     },
-    /// TODO: docs
+    /// Repeat a chart horizontally and vertically, or tile it to fill an exact size.
     Repeat {
         #[structopt(flatten)]
         args: commandargs::RepeatArgs,
     },
+    /// Replace symbols in a chart, e.g. '--from "*." --to ".*"' swaps them.
+    Replace {
+        #[structopt(flatten)]
+        args: commandargs::ReplaceArgs,
+    },
     /// Cut a chart in half and output the right side.
     Right {
         #[structopt(flatten)]
         args: commandargs::RightArgs,
     },
-    /// Cut a chart in half and output two new charts.
+    /// Shift the stitches in a chart, wrapping them around the edges.
+    Roll {
+        #[structopt(flatten)]
+        args: commandargs::RollArgs,
+    },
+    /// Scale a chart up or down, or resize it to fit a new size.
+    Scale {
+        #[structopt(flatten)]
+        args: commandargs::ScaleArgs,
+    },
+    /// Recover a chart from an image of one, such as the PNGs written by knitchart.
+    Scan {
+        #[structopt(flatten)]
+        args: commandargs::ScanArgs,
+    },
+    /// Cut a chart in half, or into panels, and output the new charts.
     Split {
         #[structopt(flatten)]
         args: commandargs::SplitArgs,
     },
+    /// Stack charts on top of each other, in order.
+    #[structopt(alias = "vconcat")]
+    Stack {
+        #[structopt(flatten)]
+        args: commandargs::StackArgs,
+    },
     Stamp {
         #[structopt(flatten)]
         args: commandargs::StampArgs,
     },
+    /// Build a symmetric chart from one half, quadrant, or wedge of it.
+    Symmetrize {
+        #[structopt(flatten)]
+        args: commandargs::SymmetrizeArgs,
+    },
     /// Trim all of the blanks and knit stitches off the outside of a chart.
     Trim {
         #[structopt(flatten)]
         args: commandargs::TrimArgs,
     },
-    /// Zip two charts together side-by-side.
+    /// Estimate the yarn needed for each color of a double-knitting chart.
+    Yarn {
+        #[structopt(flatten)]
+        args: commandargs::YarnArgs,
+    },
+    /// Zip charts together side-by-side.
     Zip {
         #[structopt(flatten)]
         args: commandargs::ZipArgs,
@@ -90,15 +250,270 @@ pub enum SubCommands {
 }
 
 #[throws]
-pub fn image_convert(args: commandargs::ImageConvertArgs) {
+pub fn check(args: commandargs::CheckArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.infile, read)?;
+    let mut highlights = Vec::new();
+
+    let limits = RunLimits {
+        max_run: args.max_run,
+        symbols: args.limit.iter().map(|l| (l.symbol, l.limit)).collect(),
+        colors: args
+            .color_limit
+            .iter()
+            .map(|l| (l.color, l.limit))
+            .collect(),
+    };
+    for run in chart.long_runs(&limits)? {
+        println!(
+            "row {}, col {}: run of {} '{}' stitches is longer than {}",
+            run.row, run.col, run.length, run.symbol, run.limit
+        );
+        highlights.push((run.row, run.col, run.length));
+    }
+
+    if args.double_knit {
+        for violation in chart.validate_double_knit()? {
+            let description = match violation.problem {
+                PairProblem::MissingColor => "a stitch has no color",
+                PairProblem::SameColor => "front and back are the same color",
+                PairProblem::WrongSides => "front and back stitches are swapped or missing",
+            };
+            println!(
+                "row {}, cols {}-{}: {}",
+                violation.row,
+                violation.col,
+                violation.col + 1,
+                description
+            );
+            highlights.push((violation.row, violation.col, 2u8.into()));
+        }
+    }
+
+    if let Some(image) = args.image {
+        the_thing_highlighted(image, &chart, &highlights)?;
+    }
+
+    if !highlights.is_empty() {
+        Err(ProblemsFound(format!(
+            "{} problems found",
+            highlights.len()
+        )))?;
+    }
+}
+
+#[throws]
+pub fn clean(args: commandargs::CleanArgs, read: &ReadOptions) {
+    let despeckle = args.despeckle.unwrap_or(0);
+    let options = CleanOptions {
+        min_region: args.min_region.unwrap_or(despeckle),
+        min_hole: args.min_hole.unwrap_or(despeckle),
+        majority: args.majority,
+    };
+    let chart = chart_in(&args.pipe.infile, read)?;
+    let (cleaned, changed) = chart.clean(&options)?;
+    eprintln!("Changed {} stitches", changed);
+    chart_out(&args.pipe.outfile, &cleaned)?;
+}
+
+#[throws]
+pub fn convert(args: commandargs::ConvertArgs, read: &ReadOptions) {
+    let chart = formatted_chart_in(args.from, &args.pipe.infile, None, read)?;
+    formatted_chart_out(args.to, &args.pipe.outfile, &chart, None)?;
+}
+
+#[throws]
+pub fn crop(args: commandargs::CropArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.pipe.infile, read)?;
+    let row = resolve_index(args.rect.row, chart.rows().into())?;
+    let col = resolve_index(args.rect.col, chart.cols().into())?;
+    let cropped = chart.crop(
+        row.try_into()?,
+        col.try_into()?,
+        args.rect.height.into(),
+        args.rect.width.into(),
+    )?;
+    chart_out(&args.pipe.outfile, &cropped)?;
+}
+
+#[throws]
+pub fn diff(args: commandargs::DiffArgs, read: &ReadOptions) {
+    let old = chart_path_in(&Some(args.old_chart), read)?;
+    let new = chart_path_in(&Some(args.new_chart), read)?;
+    let diff = old.diff(&new)?;
+    if !diff.is_empty() {
+        if !diff.same_size() {
+            eprintln!(
+                "Size changed from {}x{} to {}x{}",
+                diff.left_size.0, diff.left_size.1, diff.right_size.0, diff.right_size.1
+            );
+        }
+        if args.list {
+            for difference in &diff.differences {
+                println!(
+                    "row {}, col {}: {} -> {}",
+                    difference.row,
+                    difference.col,
+                    describe_stitch(&difference.left),
+                    describe_stitch(&difference.right)
+                );
+            }
+        } else {
+            chart_path_out(&None::<PathBuf>, &diff.mark(&new, args.marker)?)?;
+        }
+        Err(ProblemsFound(format!(
+            "{} stitches differ",
+            diff.differences.len()
+        )))?;
+    }
+}
+
+/// Describes a stitch for `diff`, including its color if it has one.
+fn describe_stitch(stitch: &Option<Stitch>) -> String {
+    match stitch {
+        None => "(none)".to_string(),
+        Some(stitch) => match stitch.color() {
+            None => format!("'{}'", stitch.symbol()),
+            Some(color) => format!(
+                "'{}' #{:02x}{:02x}{:02x}",
+                stitch.symbol(),
+                color.r,
+                color.g,
+                color.b
+            ),
+        },
+    }
+}
+
+#[throws]
+pub fn dilate(args: commandargs::DilateArgs, read: &ReadOptions) {
+    let radius = args.radius;
+    pipe_chart(args.pipe, read, |chart| chart.dilate(radius))?;
+}
+
+#[throws]
+pub fn erode(args: commandargs::ErodeArgs, read: &ReadOptions) {
+    let radius = args.radius;
+    let background = args.background;
+    pipe_chart(args.pipe, read, |chart| chart.erode(radius, background))?;
+}
+
+#[throws]
+pub fn export(args: commandargs::ExportArgs, read: &ReadOptions) {
+    if args.colors.is_some() && args.to != ChartFormat::Csv {
+        Err(anyhow!("--colors only applies to CSV"))?;
+    }
+    let chart = chart_in(&args.pipe.infile, read)?;
+    formatted_chart_out(args.to, &args.pipe.outfile, &chart, args.colors.as_deref())?;
+}
+
+#[throws]
+pub fn fill(args: commandargs::FillArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.pipe.infile, read)?;
+    let row = resolve_index(args.at.row, chart.rows().into())?;
+    let col = resolve_index(args.at.col, chart.cols().into())?;
+    let filled = chart.flood_fill(
+        row.try_into()?,
+        col.try_into()?,
+        Stitch::new(args.stitch, None),
+        args.connectivity,
+    )?;
+    chart_out(&args.pipe.outfile, &filled)?;
+}
+
+#[throws]
+pub fn halo(args: commandargs::HaloArgs, read: &ReadOptions) {
+    let radius = args.radius;
+    let halo = args.halo;
+    let outside = args.outside;
+    pipe_chart(args.pipe, read, |chart| chart.halo(radius, halo, outside))?;
+}
+
+#[throws]
+pub fn image_convert(args: commandargs::ImageConvertArgs, _read: &ReadOptions) {
     let original_image = image::open(args.image_name)?;
-    let chart = Chart::from_image(&original_image, args.height, args.width)?;
+    let mut chart = Chart::from_image(&original_image, args.height, args.width)?;
+    if let Some(size) = args.despeckle {
+        let (cleaned, changed) = chart.clean(&CleanOptions::despeckle(size))?;
+        eprintln!("Despeckling changed {} stitches", changed);
+        chart = cleaned;
+    }
     chart_out(&args.outfile, &chart)?;
 }
 
 #[throws]
-pub fn knitchart(args: commandargs::KnitchartArgs) {
-    let chart = chart_in(&args.infile)?;
+pub fn import(args: commandargs::ImportArgs, read: &ReadOptions) {
+    if args.colors.is_some() && args.from != ChartFormat::Csv {
+        Err(anyhow!("--colors only applies to CSV"))?;
+    }
+    let chart = formatted_chart_in(args.from, &args.pipe.infile, args.colors.as_deref(), read)?;
+    chart_out(&args.pipe.outfile, &chart)?;
+}
+
+#[throws]
+pub fn info(args: commandargs::InfoArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.infile, read)?;
+    let stats = chart.stats(args.connectivity)?;
+    println!("Size: {} rows x {} cols", stats.rows, stats.cols);
+
+    println!("Stitches:");
+    for ((symbol, count), (_, run)) in stats.symbol_counts.iter().zip(&stats.longest_runs) {
+        print!("  '{}': {} stitches, longest run {}", symbol, count, run);
+        match chart.describe(*symbol) {
+            Some(description) => println!(" ({})", description),
+            None => println!(),
+        }
+    }
+    if !stats.color_counts.is_empty() {
+        println!("Colors:");
+        for (color, count) in &stats.color_counts {
+            println!(
+                "  #{:02x}{:02x}{:02x}: {} stitches",
+                color.r, color.g, color.b, count
+            );
+        }
+    }
+
+    match stats.bounds {
+        Some((top, left, height, width)) => {
+            println!("Design: {}x{} at row {}, col {}", width, height, top, left)
+        }
+        None => println!("Design: empty"),
+    }
+    println!(
+        "Symmetric: left/right {}, top/bottom {}",
+        yes_no(stats.left_right_symmetric),
+        yes_no(stats.top_bottom_symmetric)
+    );
+
+    println!("Regions: {}", stats.region_count);
+    if args.regions || args.max_size.is_some() {
+        let max_size = args.max_size.unwrap_or(u32::MAX);
+        let regions = chart.regions(args.connectivity)?;
+        for region in regions.iter().filter(|region| region.size <= max_size) {
+            println!(
+                "  '{}': {} stitches at row {}, col {} ({}x{})",
+                region.symbol, region.size, region.top, region.left, region.width, region.height
+            );
+        }
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+#[throws]
+pub fn invert(args: commandargs::InvertArgs, read: &ReadOptions) {
+    pipe_chart(args.pipe, read, |chart| chart.invert())?;
+}
+
+#[throws]
+pub fn knitchart(args: commandargs::KnitchartArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.infile, read)?;
 
     // TODO: use infilename if available and not provided.
     let mut out_file = args.image_name.unwrap_or_else(|| "chart.png".into());
@@ -107,14 +522,14 @@ pub fn knitchart(args: commandargs::KnitchartArgs) {
 }
 
 #[throws]
-pub fn left(args: commandargs::LeftArgs) {
-    pipe_chart(args.pipe, |chart| Ok(chart.split()?.0))?;
+pub fn left(args: commandargs::LeftArgs, read: &ReadOptions) {
+    pipe_chart(args.pipe, read, |chart| Ok(chart.split()?.0))?;
 }
 
 #[throws]
-pub fn merge(args: commandargs::MergeArgs) {
-    let left = chart_path_in(&Some(&args.left))?;
-    let right = chart_path_in(&Some(&args.right))?;
+pub fn merge(args: commandargs::MergeArgs, read: &ReadOptions) {
+    let left = chart_path_in(&Some(&args.left), read)?;
+    let right = chart_path_in(&Some(&args.right), read)?;
 
     let merged = left.merge_with(&right)?;
     chart_path_out(&args.out_file_name, &merged)?;
@@ -125,33 +540,156 @@ pub fn merge(args: commandargs::MergeArgs) {
 }
 
 #[throws]
-pub fn pad(args: commandargs::PadArgs) {
+pub fn outline(args: commandargs::OutlineArgs, read: &ReadOptions) {
+    let background = args.background;
+    pipe_chart(args.pipe, read, |chart| chart.outline(background))?;
+}
+
+#[throws]
+pub fn pad(args: commandargs::PadArgs, read: &ReadOptions) {
     let ch = if args.purl { '*' } else { '.' };
-    let chart = chart_in(&args.pipe.infile)?;
+    let chart = chart_in(&args.pipe.infile, read)?;
     let padded = chart.pad(ch)?;
     chart_out(&args.pipe.outfile, &padded)?;
 }
 
 #[throws]
-pub fn reflect(args: commandargs::ReflectArgs) {
-    pipe_chart(args.pipe, |chart| chart.reflect())?;
+pub fn reflect(args: commandargs::ReflectArgs, read: &ReadOptions) {
+    let right_to_left = args.right_to_left;
+    pipe_chart(args.pipe, read, |chart| {
+        if right_to_left {
+            chart.reverse_reading_direction()
+        } else {
+            chart.reflect()
+        }
+    })?;
 }
 
 #[throws]
-pub fn repeat(args: commandargs::RepeatArgs) {
-    let chart = chart_in(&args.infile)?;
-    let repeated = chart.repeat(args.horiz, args.vert)?;
+pub fn repeat(args: commandargs::RepeatArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.infile, read)?;
+    let repeated = if args.width.is_none() && args.height.is_none() && !args.center {
+        chart.repeat(args.horiz, args.vert)?
+    } else {
+        // Any dimension that isn't given falls back to the size of the counted repeats.
+        let width = match args.width {
+            Some(width) => width,
+            None => (u32::from(chart.cols()) * u32::from(args.horiz)).try_into()?,
+        };
+        let height = match args.height {
+            Some(height) => height,
+            None => (u32::from(chart.rows()) * u32::from(args.vert)).try_into()?,
+        };
+        let (filled, report) = chart.repeat_to_fill(width, height, args.center)?;
+        eprintln!(
+            "Horizontal: {} full repeats, {} partial",
+            report.horiz.full, report.horiz.partial
+        );
+        eprintln!(
+            "Vertical: {} full repeats, {} partial",
+            report.vert.full, report.vert.partial
+        );
+        filled
+    };
     chart_out(&args.outfile, &repeated)?;
 }
 
 #[throws]
-pub fn right(args: commandargs::RightArgs) {
-    pipe_chart(args.pipe, |chart| Ok(chart.split()?.1))?;
+pub fn replace(args: commandargs::ReplaceArgs, read: &ReadOptions) {
+    let from: Vec<char> = args.from.chars().collect();
+    let to: Vec<char> = args.to.chars().collect();
+    if from.len() != to.len() {
+        Err(anyhow!(
+            "--from and --to must have the same number of symbols. ({} != {})",
+            from.len(),
+            to.len()
+        ))?;
+    }
+
+    let mapping: Vec<(char, char)> = from.into_iter().zip(to).collect();
+    pipe_chart(args.pipe, read, |chart| chart.replace(&mapping))?;
+}
+
+#[throws]
+pub fn right(args: commandargs::RightArgs, read: &ReadOptions) {
+    pipe_chart(args.pipe, read, |chart| Ok(chart.split()?.1))?;
+}
+
+#[throws]
+pub fn roll(args: commandargs::RollArgs, read: &ReadOptions) {
+    let (dx, dy) = (args.dx, args.dy);
+    if args.no_wrap {
+        let background = args.background;
+        pipe_chart(args.pipe, read, |chart| chart.shift(dx, dy, background))?;
+    } else {
+        pipe_chart(args.pipe, read, |chart| chart.roll(dx, dy))?;
+    }
+}
+
+#[throws]
+pub fn scale(args: commandargs::ScaleArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.pipe.infile, read)?;
+    let cols = f64::from(u32::from(chart.cols()));
+    let rows = f64::from(u32::from(chart.rows()));
+
+    let scaled = if let Some(size) = args.to {
+        if args.nearest {
+            Err(anyhow!("--nearest needs a factor of 1/N"))?;
+        }
+        // Fill in a missing dimension by keeping the chart's aspect ratio.
+        let width = size
+            .width
+            .unwrap_or_else(|| (f64::from(size.height.unwrap_or(0)) * cols / rows).round() as u16);
+        let height = size
+            .height
+            .unwrap_or_else(|| (f64::from(width) * rows / cols).round() as u16);
+        chart.resize(width, height)?
+    } else if let Some(factor) = args.factor {
+        if let (Some(h), Some(v)) = (whole_factor(factor.h), whole_factor(factor.v)) {
+            chart.scale_up(h, v)?
+        } else if let (true, Some(h), Some(v)) = (
+            args.nearest,
+            whole_factor(1.0 / factor.h),
+            whole_factor(1.0 / factor.v),
+        ) {
+            chart.scale_down(h, v)?
+        } else if args.nearest {
+            Err(anyhow!("--nearest needs a factor of 1/N"))?
+        } else {
+            chart.resize(
+                (cols * factor.h).round() as u16,
+                (rows * factor.v).round() as u16,
+            )?
+        }
+    } else {
+        Err(anyhow!("One of --factor or --to is required."))?
+    };
+    chart_out(&args.pipe.outfile, &scaled)?;
+}
+
+/// Returns `factor` as an integer if it is a whole number of at least 1.
+fn whole_factor(factor: f64) -> Option<u16> {
+    if factor >= 1.0 && (factor - factor.round()).abs() < 1e-6 {
+        Some(factor.round() as u16)
+    } else {
+        None
+    }
+}
+
+#[throws]
+pub fn scan(args: commandargs::ScanArgs, _read: &ReadOptions) {
+    let image = image::open(args.image_name)?;
+    let options = ScanOptions {
+        mark: args.mark,
+        blank: args.blank,
+    };
+    let chart = Chart::scan_image(&image, &options)?;
+    chart_out(&args.outfile, &chart)?;
 }
 
 #[throws]
-pub fn split(args: commandargs::SplitArgs) {
-    let chart = chart_in(&args.infile)?;
+pub fn split(args: commandargs::SplitArgs, read: &ReadOptions) {
+    let chart = chart_in(&args.infile, read)?;
 
     // If the out stem is provided, use it. Fallback on the input file name.
     // If that's not present (we read from stdin), then just pick "split".
@@ -163,33 +701,171 @@ pub fn split(args: commandargs::SplitArgs) {
 
     // TODO: check for existing filenames.
 
-    let left_file_name = make_knit_pathbuf(&stem, Some("-left"))?;
-    let right_file_name = make_knit_pathbuf(&stem, Some("-right"))?;
+    if args.panels.is_none() && (args.odd.is_some() || args.overlap.is_some()) {
+        Err(anyhow!("--odd and --overlap only apply with --panels"))?;
+    }
+    let odd = args.odd.unwrap_or(OddPolicy::Right);
+    let overlap = args.overlap.unwrap_or(0);
 
-    let (left_chart, right_chart) = chart.split()?;
-    left_chart.write_to_file(left_file_name)?;
-    right_chart.write_to_file(right_file_name)?;
+    let panels = match (args.panels, args.at, args.rows) {
+        (Some(n), _, false) => chart.split_into(n, odd, overlap)?,
+        (Some(n), _, true) => chart.split_rows_into(n, odd, overlap)?,
+        (None, Some(at), false) => {
+            let (left, right) = chart.split_at(at.into())?;
+            vec![left, right]
+        }
+        (None, Some(at), true) => {
+            let (top, bottom) = chart.split_at_row(at.into())?;
+            vec![top, bottom]
+        }
+        (None, None, false) => {
+            let (left, right) = chart.split()?;
+            vec![left, right]
+        }
+        (None, None, true) => chart.split_rows_into(2, odd, overlap)?,
+    };
+
+    // Two side-by-side halves keep their traditional names.
+    if panels.len() == 2 && !args.rows && args.panels.is_none() {
+        panels[0].write_to_file(make_knit_pathbuf(&stem, Some("-left"))?)?;
+        panels[1].write_to_file(make_knit_pathbuf(&stem, Some("-right"))?)?;
+    } else {
+        for (i, panel) in panels.iter().enumerate() {
+            let suffix = format!("-{}", i + 1);
+            panel.write_to_file(make_knit_pathbuf(&stem, Some(&suffix))?)?;
+        }
+    }
+}
+
+#[throws]
+pub fn stack(args: commandargs::StackArgs, read: &ReadOptions) {
+    let charts = args
+        .chart_files
+        .iter()
+        .map(|path| read_chart_file(path, read))
+        .collect::<dklib::Result<Vec<_>>>()?;
+    let chart_refs: Vec<&Chart> = charts.iter().collect();
+    let stacked = Chart::stack(&chart_refs, args.align, args.background)?;
+    chart_out(&args.outfile, &stacked)?;
 }
 
 #[throws]
-pub fn stamp(args: commandargs::StampArgs) {
-    let chart = chart_path_in(&Some(args.chart_file))?;
-    let stamp = chart_path_in(&Some(args.stamp_file))?;
-    let stamped = chart.stamp(&stamp, args.h_offset.into(), args.v_offset.into())?;
+pub fn stamp(args: commandargs::StampArgs, read: &ReadOptions) {
+    let chart = chart_path_in(&Some(args.chart_file), read)?;
+    let stamp = chart_path_in(&Some(args.stamp_file), read)?;
+    let stamped = match args.transparent {
+        Some(transparent) => chart.stamp_transparent(
+            &stamp,
+            args.h_offset.into(),
+            args.v_offset.into(),
+            transparent,
+        )?,
+        None => chart.stamp(&stamp, args.h_offset.into(), args.v_offset.into())?,
+    };
     chart_out(&args.outfile, &stamped)?;
 }
 
 #[throws]
-pub fn trim(args: commandargs::TrimArgs) {
-    pipe_chart(args.pipe, |chart| chart.trim())?;
+pub fn symmetrize(args: commandargs::SymmetrizeArgs, read: &ReadOptions) {
+    let mode = args.mode;
+    let shared_center = args.shared_center;
+    pipe_chart(args.pipe, read, |chart| {
+        chart.symmetrize(mode, shared_center)
+    })?;
 }
 
 #[throws]
-pub fn zip(args: commandargs::ZipArgs) {
-    let left_chart = Chart::read_from_file(args.left_file_name)?;
-    let right_chart = Chart::read_from_file(args.right_file_name)?;
+pub fn trim(args: commandargs::TrimArgs, read: &ReadOptions) {
+    let mut options = if args.rows {
+        TrimOptions::rows_only()
+    } else if args.cols {
+        TrimOptions::cols_only()
+    } else if !args.only.is_empty() {
+        let has = |side: &str| args.only.iter().any(|s| s == side);
+        TrimOptions {
+            top: has("top"),
+            bottom: has("bottom"),
+            left: has("left"),
+            right: has("right"),
+            ..TrimOptions::default()
+        }
+    } else {
+        TrimOptions::default()
+    };
+    options.margin = args.margin;
+    options.allow_empty = args.allow_empty;
 
-    let zipped = left_chart.zip(&right_chart)?;
+    pipe_chart(args.pipe, read, |chart| chart.trim_with(&options))?;
+}
+
+// The number of stitch pairs across `chart`. As in `Chart::estimate_yarn`, a chart that
+// already has colors is a merged chart with two columns per pair, and one without is a face.
+#[throws]
+fn pair_cols(chart: &Chart) -> u32 {
+    let cols = u32::from(chart.cols());
+    if chart.stats(Connectivity::Four)?.color_counts.is_empty() {
+        cols
+    } else {
+        cols / 2
+    }
+}
+
+#[throws]
+pub fn yarn(args: commandargs::YarnArgs, read: &ReadOptions) {
+    let front = chart_in(&args.infile, read)?;
+    let (rows, cols) = (front.rows(), pair_cols(&front)?);
+    let chart = match args.back {
+        Some(back) => front.merge_with(&chart_path_in(&Some(back), read)?)?,
+        None => front,
+    };
+
+    let yards_per_stitch = match (args.yards_per_stitch, args.swatch, args.swatch_yards) {
+        (Some(yards), _, _) => yards,
+        // Every stitch pair in a double-knit swatch is two stitches.
+        (None, Some(swatch), Some(yards)) => yards / (2.0 * swatch.stitches * swatch.rows),
+        _ => Err(anyhow!(
+            "Either --yards-per-stitch or --swatch with --swatch-yards is required"
+        ))?,
+    };
+
+    if let Some(gauge) = args.gauge {
+        println!(
+            "Finished size: {:.1} x {:.1} inches",
+            f64::from(cols) / gauge.stitches * 4.0,
+            f64::from(u32::from(rows)) / gauge.rows * 4.0
+        );
+    }
+
+    let allowance = 1.0 + args.waste / 100.0;
+    for usage in chart.estimate_yarn(yards_per_stitch)? {
+        println!(
+            "#{:02x}{:02x}{:02x}: {} stitches, {:.1} yards ({:.1} m)",
+            usage.color.r,
+            usage.color.g,
+            usage.color.b,
+            usage.stitches,
+            usage.yards * allowance,
+            usage.meters() * allowance
+        );
+    }
+}
+
+#[throws]
+pub fn zip(args: commandargs::ZipArgs, read: &ReadOptions) {
+    let charts = args
+        .chart_files
+        .iter()
+        .map(|path| read_chart_file(path, read))
+        .collect::<dklib::Result<Vec<_>>>()?;
+    let chart_refs: Vec<&Chart> = charts.iter().collect();
+
+    let options = ZipOptions {
+        gutter: args.gutter,
+        gutter_stitch: args.gutter_stitch,
+        align: args.align,
+        background: args.background,
+    };
+    let zipped = Chart::zip_all(&chart_refs, &options)?;
 
     let out_file_name = args
         .out_file_name
@@ -226,4 +902,13 @@ mod test {
 
         assert!(make_knit_pathbuf(no_file_stem, None).is_err());
     }
+
+    #[test]
+    #[throws]
+    fn test_pair_cols() {
+        let face = Chart::read(&mut "CHART\n**.\n...\n".as_bytes())?;
+        assert_eq!(pair_cols(&face)?, 3);
+        // Merging doubles the columns, but not the number of pairs.
+        assert_eq!(pair_cols(&face.merge_with(&face)?)?, 3);
+    }
 }
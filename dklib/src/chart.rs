@@ -1,30 +1,83 @@
+mod check;
+mod clean;
 mod convert;
+mod crop;
+mod diff;
+mod kind;
+mod legend;
 mod merge;
+mod morph;
 mod pad;
+mod reader;
 mod reflect;
+mod region;
 mod repeat;
+mod replace;
+mod roll;
+mod scale;
+mod scan;
+#[cfg(feature = "serde")]
+mod serde_support;
 mod split;
+mod spreadsheet;
+mod stack;
 mod stamp;
+mod stats;
+mod symmetry;
 mod trim;
+mod yarn;
 mod zip;
 
+pub use check::{LongRun, PairProblem, PairViolation, RunLimits};
+pub use clean::CleanOptions;
+pub use diff::{ChartDiff, Difference};
+pub use kind::StitchKind;
+pub use legend::LegendEntry;
+pub use reader::{ReadMode, ReadOptions, ReadWarning};
+pub use reflect::ReadingDirection;
+pub use region::{Connectivity, Region};
+pub use repeat::{FillReport, RepeatCount};
+pub use scan::ScanOptions;
+pub use split::OddPolicy;
+pub use stack::HAlign;
+pub use stats::ChartStats;
+pub use symmetry::SymmetryMode;
+pub use trim::TrimOptions;
+pub use yarn::YarnUsage;
+pub use zip::{VAlign, ZipOptions};
+
 use crate::units::{Cols, Height, Rows, Width};
-use crate::Error;
+use crate::{Error, Location, ParseError};
 use assure::assure;
 use css_color_parser::Color;
 use fehler::throws;
 use image::DynamicImage;
+use legend::parse_legend_entry;
+use reader::{check_row_length, LineReader};
 use std::{
-    cmp::max,
     convert::{TryFrom, TryInto},
     fmt::{self, Debug, Display, Formatter},
-    io::{BufRead, BufReader, Write},
+    io::{BufRead, BufReader, Read, Write},
     path::Path,
+    str::FromStr,
 };
 
-#[derive(Clone, Debug)]
+// The keys used for each color in the color section of a chart file.
+const PALETTE_KEYS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+// The way colors are written in chart files, e.g. "#daa520".
+pub(crate) fn hex_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stitch {
     symbol: char,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "serde_support::optional_color")
+    )]
     color: Option<Color>,
 }
 
@@ -63,8 +116,16 @@ impl Display for Stitch {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_support::ChartData")
+)]
 pub struct Chart {
     stitches: Vec<Vec<Stitch>>,
+    legend: Vec<LegendEntry>,
+    // None if nothing has said which way to read the chart.
+    reading_direction: Option<ReadingDirection>,
 
     rows: Rows,
     cols: Cols,
@@ -87,6 +148,8 @@ impl Chart {
 
         Chart {
             stitches,
+            legend: Vec::new(),
+            reading_direction: None,
             rows: height.into(),
             cols: width.into(),
         }
@@ -100,6 +163,58 @@ impl Chart {
         self.cols
     }
 
+    pub fn legend(&self) -> &[LegendEntry] {
+        &self.legend
+    }
+
+    pub fn set_legend(&mut self, legend: Vec<LegendEntry>) {
+        self.legend = legend;
+    }
+
+    /// The legend's description of `symbol`, if it has one.
+    pub fn describe(&self, symbol: char) -> Option<&str> {
+        self.legend
+            .iter()
+            .find(|entry| entry.symbol == symbol)
+            .map(|entry| entry.description.as_str())
+    }
+
+    /// The kind of stitch that `symbol` stands for, according to the legend.
+    pub fn kind(&self, symbol: char) -> Option<StitchKind> {
+        self.legend
+            .iter()
+            .find(|entry| entry.symbol == symbol)
+            .and_then(|entry| entry.kind)
+    }
+
+    pub fn reading_direction(&self) -> ReadingDirection {
+        self.reading_direction
+            .unwrap_or(ReadingDirection::RightToLeft)
+    }
+
+    pub fn set_reading_direction(&mut self, direction: ReadingDirection) {
+        self.reading_direction = Some(direction);
+    }
+
+    /// Copy the legend and reading direction of `source` to this chart, unless this chart
+    /// already has its own. Most operations build a new chart, and use this to keep them.
+    pub fn inherit_metadata(&mut self, source: &Chart) {
+        if self.legend.is_empty() {
+            self.legend = source.legend.clone();
+        }
+        if self.reading_direction.is_none() {
+            self.reading_direction = source.reading_direction;
+        }
+    }
+
+    /// The legend's symbol for `kind`, if it has one.
+    pub fn symbol_for(&self, kind: StitchKind) -> Option<char> {
+        self.legend
+            .iter()
+            .find(|entry| entry.kind == Some(kind))
+            .map(|entry| entry.symbol)
+    }
+
     #[throws]
     pub fn write_to_file(&self, path: impl AsRef<Path>) {
         let mut writer = std::fs::File::create(path)?;
@@ -114,6 +229,31 @@ impl Chart {
         String::from_utf8(v).unwrap()
     }
 
+    /// Write the chart in the .knit format:
+    ///
+    /// ```text
+    /// DIRECTION left-to-right
+    /// LEGEND
+    /// ○ yarn over
+    /// CHART
+    /// *○.*
+    /// .**.
+    ///
+    /// COLORS
+    /// a #daa520
+    /// b #add8e6
+    ///
+    /// abba
+    /// b..b
+    /// ```
+    ///
+    /// The header is everything before `CHART`. The `DIRECTION` and `LEGEND` lines are only
+    /// there for charts that have them. Then there is one line of stitch symbols per row.
+    ///
+    /// Charts with colors end with a footer, which is a blank line, `COLORS`, the palette of
+    /// `KEY COLOR` lines, another blank line, and a palette key (or '.' for no color) for each
+    /// stitch. A `COLORS` line that doesn't follow a blank line is an error, rather than being
+    /// read as either a row of stitches or the start of the footer.
     #[throws]
     pub fn write(&self, w: &mut dyn Write) {
         self.write_header(w)?;
@@ -123,6 +263,15 @@ impl Chart {
 
     #[throws]
     fn write_header(&self, w: &mut dyn Write) {
+        if let Some(direction) = self.reading_direction {
+            writeln!(w, "DIRECTION {}", direction)?;
+        }
+        if !self.legend.is_empty() {
+            writeln!(w, "LEGEND")?;
+            for entry in &self.legend {
+                writeln!(w, "{} {}", entry.symbol, entry.description)?;
+            }
+        }
         writeln!(w, "CHART")?
     }
 
@@ -136,66 +285,202 @@ impl Chart {
         }
     }
 
+    // Colors are written after the stitches as a blank line, `COLORS`, the palette, another
+    // blank line, and then a grid of palette keys, with '.' for stitches without a color.
+    // Charts without colors have no footer.
     #[throws]
-    fn write_footer(&self, _w: &mut dyn Write) {
-        // currently a no-op.
+    fn write_footer(&self, w: &mut dyn Write) {
+        let mut palette: Vec<Color> = Vec::new();
+        for stitch in self.stitches.iter().flatten() {
+            if let Some(color) = stitch.color() {
+                if !palette.contains(&color) {
+                    palette.push(color);
+                }
+            }
+        }
+        if palette.is_empty() {
+            return;
+        }
+        assure!(
+            palette.len() <= PALETTE_KEYS.len(),
+            Error::TooManyColors {
+                msg: "Cannot write",
+                count: palette.len()
+            }
+        );
+
+        writeln!(w)?;
+        writeln!(w, "COLORS")?;
+        let keys: Vec<char> = PALETTE_KEYS.chars().collect();
+        for (key, color) in keys.iter().zip(&palette) {
+            writeln!(w, "{} {}", key, hex_color(*color))?;
+        }
+        writeln!(w)?;
+        for row in &self.stitches {
+            for stitch in row {
+                let key = match stitch.color() {
+                    Some(color) => keys[palette.iter().position(|c| *c == color).unwrap_or(0)],
+                    None => '.',
+                };
+                write!(w, "{}", key)?;
+            }
+            writeln!(w)?;
+        }
     }
 
     #[throws]
     pub fn read_from_file(path: impl AsRef<Path>) -> Chart {
-        let file = std::fs::File::open(path)?;
+        let file = std::fs::File::open(path.as_ref())?;
         let mut rdr = BufReader::new(file);
-        Chart::read(&mut rdr)?
+        Chart::read_from(&mut rdr, Some(path.as_ref()))?
     }
 
     #[throws]
     pub fn read(rdr: &mut impl BufRead) -> Chart {
-        Chart::read_header(rdr)?;
-        let chart = Chart::read_stitches(rdr)?;
-        Chart::read_footer(rdr)?;
-        chart
+        Chart::read_from(rdr, None)?
     }
 
+    /// Like `read`, but `file` is included in the location of any parse errors.
     #[throws]
-    fn read_header(rdr: &mut impl BufRead) {
-        // Read until we find a line containing the word 'CHART'
-        let mut line = String::new();
+    pub fn read_from(rdr: &mut impl BufRead, file: Option<&Path>) -> Chart {
+        Chart::read_with(rdr, file, &ReadOptions::default())?.0
+    }
+
+    /// Like `read_from`, but with control over how strict the reading is.
+    /// Returns any warnings along with the chart.
+    #[throws]
+    pub fn read_with(
+        rdr: &mut impl BufRead,
+        file: Option<&Path>,
+        options: &ReadOptions,
+    ) -> (Chart, Vec<ReadWarning>) {
+        let mut reader = LineReader::new(rdr, file);
+        let mut warnings = Vec::new();
+        let (legend, reading_direction) = Chart::read_header(&mut reader)?;
+        let mut chart = Chart::read_stitches(&mut reader, options, &mut warnings)?;
+        chart.legend = legend;
+        chart.reading_direction = reading_direction;
+        Chart::read_footer(&mut reader, &mut chart)?;
+        (chart, warnings)
+    }
 
+    /// Read a chart from CSV with one cell per stitch. The first character of each cell is the
+    /// symbol, and anything after it is the color, e.g. `*#daa520`. Empty cells are '.'.
+    /// Returns any warnings along with the chart.
+    #[throws]
+    pub fn read_csv(
+        rdr: impl Read,
+        file: Option<&Path>,
+        options: &ReadOptions,
+    ) -> (Chart, Vec<ReadWarning>) {
+        spreadsheet::read_csv(rdr, file, options)?
+    }
+
+    /// Color the stitches from a CSV grid of colors, one cell per stitch.
+    /// Empty cells leave the stitch's color alone.
+    #[throws]
+    pub fn read_csv_colors(&mut self, rdr: impl Read, file: Option<&Path>) {
+        spreadsheet::read_csv_colors(self, rdr, file)?
+    }
+
+    /// Write the chart as CSV, with the colors in the cells if `inline_colors` is set.
+    #[throws]
+    pub fn write_csv(&self, w: &mut dyn Write, inline_colors: bool) {
+        spreadsheet::write_csv(self, w, inline_colors)?
+    }
+
+    /// Write the colors of the chart as a CSV grid, to go with `write_csv`.
+    #[throws]
+    pub fn write_csv_colors(&self, w: &mut dyn Write) {
+        spreadsheet::write_csv_colors(self, w)?
+    }
+
+    // Returns the legend and the reading direction, if the header has them.
+    #[throws]
+    fn read_header(
+        reader: &mut LineReader<impl BufRead>,
+    ) -> (Vec<LegendEntry>, Option<ReadingDirection>) {
+        let mut legend = Vec::new();
+        let mut reading_direction = None;
+        let mut in_legend = false;
+        // Read until we find a line containing the word 'CHART'
         loop {
-            line.clear();
-            let size = rdr.read_line(&mut line)?;
-            assure!(size > 0, Error::IncompleteHeader);
+            if !reader.next_line()? {
+                Err(reader.error(1, ParseError::IncompleteHeader))?;
+            }
+            let line = reader.line();
             if line.starts_with("CHART") {
                 break;
             }
+            if let Some(value) = line.strip_prefix("DIRECTION ") {
+                let value = value.trim();
+                match ReadingDirection::from_str(value) {
+                    Ok(direction) => reading_direction = Some(direction),
+                    Err(_) => Err(reader.error(
+                        "DIRECTION ".len() + 1,
+                        ParseError::BadDirection {
+                            value: value.to_string(),
+                        },
+                    ))?,
+                }
+            } else if line == "LEGEND" {
+                in_legend = true;
+            } else if in_legend && !line.is_empty() {
+                match parse_legend_entry(line) {
+                    Some(entry) => legend.push(entry),
+                    None => Err(reader.error(1, ParseError::BadLegendEntry))?,
+                }
+            }
         }
+        (legend, reading_direction)
     }
 
     #[throws]
-    fn read_stitches(rdr: &mut impl BufRead) -> Chart {
-        let mut max_cols = 0;
-
-        let mut line = String::new();
-        let mut stitches = Vec::new();
-        loop {
-            line.clear();
-            let size = rdr.read_line(&mut line)?;
-            if size == 0 {
-                // File's done!
-                break;
+    fn read_stitches(
+        reader: &mut LineReader<impl BufRead>,
+        options: &ReadOptions,
+        warnings: &mut Vec<ReadWarning>,
+    ) -> Chart {
+        let mut stitches: Vec<Vec<Stitch>> = Vec::new();
+        // A blank line is either a row without stitches or the end of the stitches, depending
+        // on whether the color footer comes next.
+        let mut blank_line: Option<Location> = None;
+        while reader.next_line()? {
+            let stitch_str = reader.line();
+            if stitch_str == "COLORS" {
+                if blank_line.take().is_some() {
+                    // The blank line and the rest belong to the footer.
+                    break;
+                }
+                Err(reader.error(1, ParseError::UnseparatedFooter))?;
             }
 
-            let stitch_str = line.trim_end_matches('\n');
-            max_cols = max(stitch_str.len(), max_cols);
-
-            let mut current_row = Vec::new();
-            for ch in stitch_str.chars() {
-                let stitch = Stitch::new(ch, None);
-                current_row.push(stitch);
+            if let Some(location) = blank_line.take() {
+                push_row(&mut stitches, "", |_| location, options, warnings)?;
             }
-            stitches.push(current_row);
+            if stitch_str.is_empty() {
+                blank_line = Some(reader.location(1));
+            } else {
+                push_row(
+                    &mut stitches,
+                    stitch_str,
+                    |column| reader.location(column),
+                    options,
+                    warnings,
+                )?;
+            }
+        }
+        if let Some(location) = blank_line {
+            push_row(&mut stitches, "", |_| location, options, warnings)?;
         }
 
+        Chart::from_rows(stitches)?
+    }
+
+    // Build a chart from rows of stitches, padding short rows to the length of the longest.
+    #[throws]
+    fn from_rows(mut stitches: Vec<Vec<Stitch>>) -> Chart {
+        let max_cols = stitches.iter().map(Vec::len).max().unwrap_or(0);
         for line in &mut stitches {
             // TODO: we are defaulting to '.' here, but that's wrong.
             //       we *should* default to something knowable, but on the chart.
@@ -206,14 +491,64 @@ impl Chart {
         let rows = Rows::try_from(stitches.len())?;
         Chart {
             stitches,
+            legend: Vec::new(),
+            reading_direction: None,
             rows,
             cols: max_cols.try_into()?,
         }
     }
 
+    // Reads the colors written by `write_footer`, if there are any.
     #[throws]
-    fn read_footer(_r: &mut impl BufRead) {
-        // currently a no-op
+    fn read_footer(reader: &mut LineReader<impl BufRead>, chart: &mut Chart) {
+        let mut palette: Vec<(char, Color)> = Vec::new();
+        loop {
+            if !reader.next_line()? {
+                return;
+            }
+            let entry = reader.line();
+            if entry.trim().is_empty() {
+                break;
+            }
+            let mut parts = entry.splitn(2, ' ');
+            let key_part = parts.next().unwrap_or("");
+            let rest = parts.next().unwrap_or("");
+            let key = key_part.chars().next();
+            let value = rest.trim();
+            // The value starts after the key, the space, and any more leading whitespace.
+            let value_column = key_part.chars().count() + 1 + rest.chars().count()
+                - rest.trim_start().chars().count()
+                + 1;
+            let key = match key {
+                Some(key) if !value.is_empty() => key,
+                _ => Err(reader.error(1, ParseError::BadPaletteEntry))?,
+            };
+            let color = match Color::from_str(value) {
+                Ok(color) => color,
+                Err(_) => Err(reader.error(
+                    value_column,
+                    ParseError::BadColor {
+                        value: value.to_string(),
+                    },
+                ))?,
+            };
+            palette.push((key, color));
+        }
+
+        for row in chart.stitches.iter_mut() {
+            if !reader.next_line()? {
+                break;
+            }
+            for (index, (stitch, key)) in row.iter_mut().zip(reader.line().chars()).enumerate() {
+                if key == '.' {
+                    continue;
+                }
+                match palette.iter().find(|(k, _)| *k == key) {
+                    Some((_, color)) => stitch.color = Some(*color),
+                    None => Err(reader.error(index + 1, ParseError::UnknownColorKey { key }))?,
+                }
+            }
+        }
     }
 
     #[throws]
@@ -257,16 +592,83 @@ impl AsRef<Chart> for Chart {
 }
 
 impl Chart {
+    #[throws]
+    pub fn diff(&self, other: &Chart) -> ChartDiff {
+        diff::diff_charts(self, other)?
+    }
+
+    #[throws]
+    pub fn dilate(&self, radius: u16) -> Chart {
+        morph::dilate_chart(self, radius)?
+    }
+
+    #[throws]
+    pub fn erode(&self, radius: u16, background: char) -> Chart {
+        morph::erode_chart(self, radius, background)?
+    }
+
+    #[throws]
+    pub fn estimate_yarn(&self, yards_per_stitch: f64) -> Vec<YarnUsage> {
+        yarn::estimate_yarn(self, yards_per_stitch)?
+    }
+
+    #[throws]
+    pub fn flood_fill(
+        &self,
+        row: Rows,
+        col: Cols,
+        stitch: Stitch,
+        connectivity: Connectivity,
+    ) -> Chart {
+        region::flood_fill(self, row, col, stitch, connectivity)?
+    }
+
     #[throws]
     pub fn from_image(image: &DynamicImage, height: Option<u16>, width: Option<u16>) -> Chart {
         convert::convert_image_to_chart(image, height, width)?
     }
 
+    /// Read a chart back out of a picture of a chart, such as one written by `the_thing`.
+    #[throws]
+    pub fn scan_image(image: &DynamicImage, options: &ScanOptions) -> Chart {
+        scan::scan_chart_image(image, options)?
+    }
+
+    #[throws]
+    pub fn clean(&self, options: &CleanOptions) -> (Chart, u32) {
+        clean::clean_chart(self, options)?
+    }
+
+    #[throws]
+    pub fn crop(&self, row: Rows, col: Cols, height: Height, width: Width) -> Chart {
+        crop::crop_chart(self, row, col, height, width)?
+    }
+
+    #[throws]
+    pub fn halo(&self, radius: u16, halo: char, outside: char) -> Chart {
+        morph::halo_chart(self, radius, halo, outside)?
+    }
+
+    #[throws]
+    pub fn invert(&self) -> Chart {
+        replace::invert_chart(self)?
+    }
+
+    #[throws]
+    pub fn long_runs(&self, limits: &RunLimits) -> Vec<LongRun> {
+        check::find_long_runs(self, limits)?
+    }
+
     #[throws]
     pub fn merge_with(&self, other: &Chart) -> Chart {
         merge::merge_charts(self, other)?
     }
 
+    #[throws]
+    pub fn outline(&self, background: char) -> Chart {
+        morph::outline_chart(self, background)?
+    }
+
     #[throws]
     pub fn pad(&self, ch: char) -> Chart {
         pad::pad_chart(self, ch)?
@@ -277,30 +679,167 @@ impl Chart {
         reflect::reflect_chart(self)?
     }
 
+    /// Mirror the chart so that it can be read in the other direction, e.g. left to right by a
+    /// left-handed knitter.
+    #[throws]
+    pub fn reverse_reading_direction(&self) -> Chart {
+        reflect::reverse_reading_direction(self)?
+    }
+
+    #[throws]
+    pub fn regions(&self, connectivity: Connectivity) -> Vec<Region> {
+        region::label_regions(self, connectivity)?
+    }
+
     #[throws]
     pub fn repeat(&self, h: u8, v: u8) -> Chart {
         repeat::repeat_chart(self, h, v)?
     }
 
+    #[throws]
+    pub fn repeat_to_fill<W, H>(&self, width: W, height: H, centered: bool) -> (Chart, FillReport)
+    where
+        W: Into<Width>,
+        H: Into<Height>,
+    {
+        repeat::repeat_to_fill(self, width.into(), height.into(), centered)?
+    }
+
+    #[throws]
+    pub fn resize<W, H>(&self, width: W, height: H) -> Chart
+    where
+        W: Into<Width>,
+        H: Into<Height>,
+    {
+        scale::resize_chart(self, width.into(), height.into())?
+    }
+
+    #[throws]
+    pub fn replace(&self, mapping: &[(char, char)]) -> Chart {
+        replace::replace_symbols(self, mapping)?
+    }
+
+    #[throws]
+    pub fn roll(&self, dx: i32, dy: i32) -> Chart {
+        roll::roll_chart(self, dx, dy)?
+    }
+
+    #[throws]
+    pub fn scale_down(&self, h: u16, v: u16) -> Chart {
+        scale::scale_down(self, h, v)?
+    }
+
+    #[throws]
+    pub fn scale_up(&self, h: u16, v: u16) -> Chart {
+        scale::scale_up(self, h, v)?
+    }
+
+    #[throws]
+    pub fn shift(&self, dx: i32, dy: i32, background: char) -> Chart {
+        roll::shift_chart(self, dx, dy, background)?
+    }
+
     #[throws]
     pub fn split(&self) -> (Chart, Chart) {
         split::split_chart(self)?
     }
 
+    #[throws]
+    pub fn split_at(&self, col: Cols) -> (Chart, Chart) {
+        split::split_at(self, col)?
+    }
+
+    #[throws]
+    pub fn split_at_row(&self, row: Rows) -> (Chart, Chart) {
+        split::split_at_row(self, row)?
+    }
+
+    #[throws]
+    pub fn split_into(&self, n: u16, policy: OddPolicy, overlap: u16) -> Vec<Chart> {
+        split::split_into(self, n, policy, overlap)?
+    }
+
+    #[throws]
+    pub fn split_rows_into(&self, n: u16, policy: OddPolicy, overlap: u16) -> Vec<Chart> {
+        split::split_rows_into(self, n, policy, overlap)?
+    }
+
+    #[throws]
+    pub fn stack(charts: &[&Chart], align: HAlign, background: char) -> Chart {
+        stack::stack_charts(charts, align, background)?
+    }
+
     #[throws]
     pub fn stamp(&self, stamp: &Chart, h_offset: Cols, v_offset: Rows) -> Chart {
         stamp::stamp_chart(self, stamp, h_offset, v_offset)?
     }
 
+    #[throws]
+    pub fn stamp_transparent(
+        &self,
+        stamp: &Chart,
+        h_offset: Cols,
+        v_offset: Rows,
+        transparent: char,
+    ) -> Chart {
+        stamp::stamp_chart_with(self, stamp, h_offset, v_offset, Some(transparent))?
+    }
+
+    #[throws]
+    pub fn stats(&self, connectivity: Connectivity) -> ChartStats {
+        stats::chart_stats(self, connectivity)?
+    }
+
+    #[throws]
+    pub fn symmetrize(&self, mode: SymmetryMode, shared_center: bool) -> Chart {
+        symmetry::symmetrize_chart(self, mode, shared_center)?
+    }
+
     #[throws]
     pub fn trim(&self) -> Chart {
         trim::trim_chart(self)?
     }
 
+    #[throws]
+    pub fn trim_with(&self, options: &TrimOptions) -> Chart {
+        trim::trim_chart_with(self, options)?
+    }
+
+    #[throws]
+    pub fn validate_double_knit(&self) -> Vec<PairViolation> {
+        check::validate_double_knit(self)?
+    }
+
     #[throws]
     pub fn zip(&self, right: &Chart) -> Chart {
-        zip::zip_charts(self, right)?
+        zip::zip_charts(&[self, right], &ZipOptions::default())?
     }
+
+    #[throws]
+    pub fn zip_all(charts: &[&Chart], options: &ZipOptions) -> Chart {
+        zip::zip_charts(charts, options)?
+    }
+}
+
+// Add a row of stitches read from `text`, checking its length against the first row.
+#[throws]
+fn push_row(
+    stitches: &mut Vec<Vec<Stitch>>,
+    text: &str,
+    location: impl FnOnce(usize) -> Location,
+    options: &ReadOptions,
+    warnings: &mut Vec<ReadWarning>,
+) {
+    if let Some(first_row) = stitches.first() {
+        check_row_length(
+            options,
+            first_row.len(),
+            text.chars().count(),
+            location,
+            warnings,
+        )?;
+    }
+    stitches.push(text.chars().map(|ch| Stitch::new(ch, None)).collect());
 }
 
 #[rustfmt::skip::macros(chart, chart_str)]
@@ -346,6 +885,207 @@ mod test {
         assert_eq!(chart_out, chart_in);
     }
 
+    #[test]
+    fn test_read_errors() {
+        let bad_key = chart_str!(
+            "**",
+            "",
+            "COLORS",
+            "a #ff0000",
+            "",
+            "ab");
+        match Chart::read_from(
+            &mut BufReader::new(bad_key.as_bytes()),
+            Some(Path::new("x.knit")),
+        ) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::UnknownColorKey { key },
+            }) => {
+                assert_eq!(key, 'b');
+                assert_eq!((location.line, location.column), (7, 2));
+                assert_eq!(location.to_string(), "x.knit:7:2");
+                assert_eq!(location.snippet(), "7 | ab\n  |  ^");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let bad_color = chart_str!(
+            "**",
+            "",
+            "COLORS",
+            "a notacolor");
+        match Chart::read(&mut BufReader::new(bad_color.as_bytes())) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::BadColor { .. },
+            }) => {
+                assert_eq!((location.line, location.column), (5, 3));
+                assert_eq!(location.to_string(), "line 5, column 3");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // The column is that of the color, even when the key looks the same.
+        let key_color = chart_str!(
+            "**",
+            "",
+            "COLORS",
+            "b  b");
+        match Chart::read(&mut BufReader::new(key_color.as_bytes())) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::BadColor { .. },
+            }) => assert_eq!((location.line, location.column), (5, 4)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let no_chart = "LEGEND\n○ yarn over\n";
+        match Chart::read(&mut BufReader::new(no_chart.as_bytes())) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::IncompleteHeader,
+            }) => assert_eq!((location.line, location.column), (3, 1)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let bad_entry = chart_str!(
+            "**",
+            "",
+            "COLORS",
+            "a");
+        assert!(Chart::read(&mut BufReader::new(bad_entry.as_bytes())).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_footer_separator() {
+        // Without the blank line, "COLORS" could be a row of stitches or the footer.
+        let unseparated = chart_str!(
+            "**",
+            "COLORS",
+            "a #ff0000");
+        match Chart::read(&mut BufReader::new(unseparated.as_bytes())) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::UnseparatedFooter,
+            }) => assert_eq!((location.line, location.column), (3, 1)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // A blank line that isn't followed by the footer is a row without stitches.
+        let blank_row = chart_str!(
+            "**",
+            "",
+            "**");
+        let chart = Chart::read(&mut BufReader::new(blank_row.as_bytes()))?;
+        assert_eq!(chart.write_to_string()?, chart_str!("**", "..", "**"));
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_ragged() {
+        let ragged = chart_str!(
+            "***",
+            "*",
+            "****");
+        let read = |mode| {
+            Chart::read_with(
+                &mut BufReader::new(ragged.as_bytes()),
+                None,
+                &ReadOptions { mode },
+            )
+        };
+
+        let (chart, warnings) = read(ReadMode::Lenient)?;
+        assert_eq!(chart.cols(), Cols::from(4u8));
+        assert!(warnings.is_empty());
+
+        let (chart, warnings) = read(ReadMode::Warn)?;
+        assert_eq!(chart.cols(), Cols::from(4u8));
+        let found: Vec<(usize, usize)> = warnings
+            .iter()
+            .map(|w| (w.location.line, w.location.column))
+            .collect();
+        assert_eq!(found, vec![(3, 2), (4, 4)]);
+
+        match read(ReadMode::Strict) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::RaggedRow { expected, found },
+            }) => {
+                assert_eq!((expected, found), (3, 1));
+                assert_eq!(location.to_string(), "line 3, column 2");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // Rows are measured in characters, not bytes.
+        let wide = chart_str!(
+            "é*",
+            "*é");
+        let options = ReadOptions {
+            mode: ReadMode::Strict,
+        };
+        let (chart, _) = Chart::read_with(&mut BufReader::new(wide.as_bytes()), None, &options)?;
+        assert_eq!(chart.cols(), Cols::from(2u8));
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_write_legend() {
+        let text = "LEGEND\n○ yarn over\n╱ k2tog\nCHART\n○╱*\n*○╱\n";
+        let chart = Chart::read(&mut BufReader::new(text.as_bytes()))?;
+        assert_eq!(chart.cols(), Cols::from(3u8));
+        assert_eq!(chart.stitch(0u8.into(), 1u8.into())?.symbol(), '╱');
+        assert_eq!(chart.describe('○'), Some("yarn over"));
+        assert_eq!(chart.describe('*'), None);
+        assert_eq!(chart.write_to_string()?, text);
+
+        // Header lines before the legend are still ignored.
+        let bad = "a comment\nLEGEND\n○\nCHART\n○\n";
+        match Chart::read(&mut BufReader::new(bad.as_bytes())) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::BadLegendEntry,
+            }) => assert_eq!(location.line, 3),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_write_colors() {
+        let chart = chart!(
+            "*.",
+            ".*")?;
+        let mut merged = chart.merge_with(&chart)?;
+        merged.set_stitch(1u8.into(), 3u8.into(), Stitch::new('x', None))?;
+
+        let written = merged.write_to_string()?;
+        assert_eq!(
+            written,
+            chart_str!(
+                "* * ",
+                "* *x",
+                "",
+                "COLORS",
+                "a #daa520",
+                "b #add8e6",
+                "",
+                "abba",
+                "baa."
+            )
+        );
+
+        let read = Chart::read(&mut BufReader::new(written.as_bytes()))?;
+        assert_eq!(read.write_to_string()?, written);
+        assert_eq!(
+            read.stitch(0u8.into(), 0u8.into())?,
+            merged.stitch(0u8.into(), 0u8.into())?
+        );
+    }
+
     #[throws]
     #[test]
     fn test_missing_stitches() {
@@ -0,0 +1,315 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows, Width};
+use crate::Error;
+use assure::assure;
+use css_color_parser::Color;
+use fehler::throws;
+use std::convert::TryFrom;
+
+/// The longest runs allowed by `Chart::long_runs`.
+///
+/// Colored stitches are looked up in `colors` first. Stitches without a color limit are looked
+/// up in `symbols`, using the symbol of the first stitch in the run. Anything not listed there
+/// is limited by `max_run`, or not limited at all if that is `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunLimits {
+    pub max_run: Option<u32>,
+    pub symbols: Vec<(char, u32)>,
+    pub colors: Vec<(Color, u32)>,
+}
+
+impl RunLimits {
+    /// Limit every run to `max_run` stitches.
+    pub fn new(max_run: u32) -> RunLimits {
+        RunLimits {
+            max_run: Some(max_run),
+            ..RunLimits::default()
+        }
+    }
+
+    fn limit_for(&self, stitch: &Stitch) -> Option<u32> {
+        let by_color = stitch
+            .color()
+            .and_then(|color| self.colors.iter().find(|(c, _)| *c == color))
+            .map(|(_, l)| *l);
+        let by_symbol = || {
+            self.symbols
+                .iter()
+                .find(|(s, _)| *s == stitch.symbol())
+                .map(|(_, l)| *l)
+        };
+        by_color.or_else(by_symbol).or(self.max_run)
+    }
+}
+
+/// A horizontal run of stitches that is longer than allowed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LongRun {
+    pub row: Rows,
+    /// The leftmost column of the run.
+    pub col: Cols,
+    pub length: Width,
+    pub symbol: char,
+    pub color: Option<Color>,
+    pub limit: u32,
+}
+
+// Find every run of stitches in a row that is longer than `limits` allows.
+//
+// A run is a group of adjacent stitches with the same color, or, for stitches without a color,
+// the same symbol.
+#[throws]
+pub fn find_long_runs(chart: &Chart, limits: &RunLimits) -> Vec<LongRun> {
+    let mut long_runs = Vec::new();
+    for (r, row) in chart.stitches.iter().enumerate() {
+        let mut start = 0;
+        while start < row.len() {
+            let first = &row[start];
+            let mut end = start + 1;
+            while end < row.len() && same_yarn(first, &row[end]) {
+                end += 1;
+            }
+
+            if let Some(limit) = limits.limit_for(first) {
+                if end - start > limit as usize {
+                    long_runs.push(LongRun {
+                        row: Rows::try_from(r)?,
+                        col: Cols::try_from(start)?,
+                        length: Width::try_from(end - start)?,
+                        symbol: first.symbol(),
+                        color: first.color(),
+                        limit,
+                    });
+                }
+            }
+            start = end;
+        }
+    }
+    long_runs
+}
+
+fn same_yarn(a: &Stitch, b: &Stitch) -> bool {
+    match (a.color(), b.color()) {
+        (Some(a_color), Some(b_color)) => a_color == b_color,
+        (None, None) => a.symbol() == b.symbol(),
+        _ => false,
+    }
+}
+
+/// The ways that a pair of columns in a merged chart can be broken.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PairProblem {
+    /// One or both of the stitches has no color.
+    MissingColor,
+    /// The front and back stitches are the same color.
+    SameColor,
+    /// The stitches are not marked as front ('*') and back (' '), in that order.
+    WrongSides,
+}
+
+/// A stitch pair that breaks the structure of a merged chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairViolation {
+    pub row: Rows,
+    /// The column of the front stitch. The back stitch is in the next column.
+    pub col: Cols,
+    pub problem: PairProblem,
+}
+
+// Check that `chart` has the structure that `merge_charts` produces: columns alternate between
+// the front ('*') and back (' ') stitch of each pair, and the two stitches in a pair are always
+// opposite colors out of two.
+//
+// Returns every pair that breaks the structure, with at most one problem per pair.
+#[throws]
+pub fn validate_double_knit(chart: &Chart) -> Vec<PairViolation> {
+    assure!(
+        u32::from(chart.cols()) % 2 == 0,
+        Error::OddWidth {
+            msg: "Cannot validate",
+            cols: chart.cols().into()
+        }
+    );
+
+    let mut colors = Vec::new();
+    for color in chart.stitches.iter().flatten().filter_map(|s| s.color()) {
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+    assure!(
+        colors.len() <= 2,
+        Error::TooManyColors {
+            msg: "Cannot validate",
+            count: colors.len()
+        }
+    );
+
+    let mut violations = Vec::new();
+    for (r, row) in chart.stitches.iter().enumerate() {
+        for (pair, stitches) in row.chunks(2).enumerate() {
+            let (front, back) = (&stitches[0], &stitches[1]);
+            let problem = match (front.color(), back.color()) {
+                (None, _) | (_, None) => Some(PairProblem::MissingColor),
+                (Some(front_color), Some(back_color)) if front_color == back_color => {
+                    Some(PairProblem::SameColor)
+                }
+                _ if front.symbol() != '*' || back.symbol() != ' ' => Some(PairProblem::WrongSides),
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                violations.push(PairViolation {
+                    row: Rows::try_from(r)?,
+                    col: Cols::try_from(pair * 2)?,
+                    problem,
+                });
+            }
+        }
+    }
+    violations
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[throws]
+    #[test]
+    fn test_long_runs() {
+        let chart = chart!(
+            "****..*",
+            "*.*.*.*",
+            "......."
+        )?;
+
+        let runs = find_long_runs(&chart, &RunLimits::new(3))?;
+        let found: Vec<(Rows, Cols, Width, char)> = runs
+            .iter()
+            .map(|run| (run.row, run.col, run.length, run.symbol))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0u8.into(), 0u8.into(), 4u8.into(), '*'),
+                (2u8.into(), 0u8.into(), 7u8.into(), '.'),
+            ]
+        );
+
+        assert!(find_long_runs(&chart, &RunLimits::default())?.is_empty());
+    }
+
+    #[throws]
+    #[test]
+    fn test_long_runs_per_symbol() {
+        let chart = chart!(
+            "****..*",
+            "......."
+        )?;
+
+        let limits = RunLimits {
+            max_run: Some(3),
+            symbols: vec![('.', 10)],
+            ..RunLimits::default()
+        };
+        let runs = find_long_runs(&chart, &limits)?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].limit, 3);
+    }
+
+    #[throws]
+    #[test]
+    fn test_long_runs_colors() {
+        let chart = chart!(
+            "*...",
+            "...."
+        )?;
+        let merged = chart.merge_with(&chart)?;
+
+        // Row 0 is gold, blue, blue, gold, ... since the purl's back stitch is next to a knit.
+        let blue = Color::from_str("lightblue")?;
+        let limits = RunLimits {
+            colors: vec![(blue, 1)],
+            ..RunLimits::default()
+        };
+        let runs = find_long_runs(&merged, &limits)?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].row, runs[0].col), (0u8.into(), 1u8.into()));
+        assert_eq!((runs[0].length, runs[0].color), (2u8.into(), Some(blue)));
+
+        let limits = RunLimits {
+            colors: vec![(blue, 2)],
+            ..RunLimits::default()
+        };
+        assert!(find_long_runs(&merged, &limits)?.is_empty());
+    }
+
+    #[throws]
+    #[test]
+    fn test_long_runs_merged_symbols() {
+        let chart = chart!(
+            "*...",
+            "...."
+        )?;
+        let merged = chart.merge_with(&chart)?;
+
+        // Without a color limit, a colored run is limited by the symbol it starts with. Row 0 has
+        // a run of two blue stitches, starting with the back stitch of the knit.
+        let limits = RunLimits {
+            symbols: vec![(' ', 1)],
+            ..RunLimits::default()
+        };
+        let runs = find_long_runs(&merged, &limits)?;
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].row, runs[0].col), (0u8.into(), 1u8.into()));
+        assert_eq!((runs[0].length, runs[0].limit), (2u8.into(), 1));
+
+        // A color limit wins over the symbol limit.
+        let limits = RunLimits {
+            symbols: vec![(' ', 1)],
+            colors: vec![(Color::from_str("lightblue")?, 2)],
+            ..RunLimits::default()
+        };
+        assert!(find_long_runs(&merged, &limits)?.is_empty());
+    }
+
+    #[throws]
+    #[test]
+    fn test_validate_double_knit() {
+        let chart = chart!(
+            "*..*",
+            ".**."
+        )?;
+        let merged = chart.merge_with(&chart)?;
+        assert!(validate_double_knit(&merged)?.is_empty());
+
+        let mut broken = merged.clone();
+        let front = broken.stitch(1u8.into(), 2u8.into())?.clone();
+        broken.set_stitch(1u8.into(), 3u8.into(), front)?;
+        broken.set_stitch(0u8.into(), 6u8.into(), Stitch::new('.', None))?;
+        let swapped = Stitch::new(' ', merged.stitch(0u8.into(), 0u8.into())?.color());
+        broken.set_stitch(0u8.into(), 0u8.into(), swapped)?;
+
+        let violations = validate_double_knit(&broken)?;
+        let found: Vec<(Rows, Cols, PairProblem)> = violations
+            .iter()
+            .map(|v| (v.row, v.col, v.problem))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0u8.into(), 0u8.into(), PairProblem::WrongSides),
+                (0u8.into(), 6u8.into(), PairProblem::MissingColor),
+                (1u8.into(), 2u8.into(), PairProblem::SameColor),
+            ]
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_validate_double_knit_odd_width() {
+        let chart = chart!("***")?;
+        assert!(validate_double_knit(&chart).is_err());
+    }
+}
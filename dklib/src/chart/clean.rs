@@ -0,0 +1,210 @@
+use crate::chart::region::{collect_region, Connectivity};
+use crate::chart::{Chart, Stitch};
+use crate::Error;
+use fehler::throws;
+use std::convert::TryFrom;
+
+/// Settings for `Chart::clean`. The default settings leave the chart alone.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CleanOptions {
+    /// Shapes with fewer than this many stitches are merged into their surroundings.
+    pub min_region: u32,
+    /// Holes (regions of empty stitches) with fewer than this many stitches are filled in.
+    pub min_hole: u32,
+    /// Replace each stitch with the stitch that fills most of its 3x3 neighborhood.
+    pub majority: bool,
+}
+
+impl CleanOptions {
+    /// Remove shapes and fill holes with fewer than `size` stitches.
+    pub fn despeckle(size: u32) -> CleanOptions {
+        CleanOptions {
+            min_region: size,
+            min_hole: size,
+            majority: false,
+        }
+    }
+}
+
+// Remove the noise from `chart`, typically after converting it from an image.
+// Returns the cleaned chart and the number of stitches that changed.
+//
+// Small shapes and holes are replaced by the stitch that borders them most often, and then the
+// majority filter, if requested, is run over the result.
+#[throws]
+pub fn clean_chart(chart: &Chart, options: &CleanOptions) -> (Chart, u32) {
+    let mut cleaned = merge_small_regions(chart, options);
+    if options.majority {
+        cleaned = majority_filter(&cleaned);
+    }
+
+    let mut changed = 0usize;
+    for (old_row, new_row) in chart.stitches.iter().zip(&cleaned.stitches) {
+        changed += old_row.iter().zip(new_row).filter(|(o, n)| o != n).count();
+    }
+    (cleaned, u32::try_from(changed)?)
+}
+
+fn merge_small_regions(chart: &Chart, options: &CleanOptions) -> Chart {
+    let rows = chart.stitches.len();
+    let cols = usize::from(chart.cols);
+
+    let mut merged = chart.clone();
+    let mut seen = vec![vec![false; cols]; rows];
+    for row in chart.rows() {
+        for col in chart.cols() {
+            let (r, c) = (usize::from(row), usize::from(col));
+            if seen[r][c] {
+                continue;
+            }
+
+            let target = &chart.stitches[r][c];
+            let members = collect_region(chart, row, col, Connectivity::Four, &mut seen);
+            let min_size = if target.is_empty() {
+                options.min_hole
+            } else {
+                options.min_region
+            };
+            if members.len() >= min_size as usize {
+                continue;
+            }
+
+            // Count the stitches bordering the region. Ties go to the first one found.
+            let mut votes: Vec<(&Stitch, u32)> = Vec::new();
+            for (mr, mc) in &members {
+                for (nr, nc) in neighbors(*mr, *mc, rows, cols) {
+                    let stitch = &chart.stitches[nr][nc];
+                    if stitch == target {
+                        continue;
+                    }
+                    match votes.iter_mut().find(|(s, _)| *s == stitch) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((stitch, 1)),
+                    }
+                }
+            }
+
+            if let Some((replacement, _)) = votes.iter().rev().max_by_key(|(_, count)| *count) {
+                for (mr, mc) in members {
+                    merged.stitches[mr][mc] = (*replacement).clone();
+                }
+            }
+        }
+    }
+    merged
+}
+
+// Replace each stitch with the stitch that makes up more than half of its 3x3 neighborhood.
+// Stitches without a clear majority are left alone.
+fn majority_filter(chart: &Chart) -> Chart {
+    let rows = chart.stitches.len();
+    let cols = usize::from(chart.cols);
+
+    let mut filtered = chart.clone();
+    for r in 0..rows {
+        for c in 0..cols {
+            let mut votes: Vec<(&Stitch, usize)> = Vec::new();
+            let mut total = 0;
+            for nr in r.saturating_sub(1)..usize::min(r + 2, rows) {
+                for nc in c.saturating_sub(1)..usize::min(c + 2, cols) {
+                    let stitch = &chart.stitches[nr][nc];
+                    total += 1;
+                    match votes.iter_mut().find(|(s, _)| *s == stitch) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((stitch, 1)),
+                    }
+                }
+            }
+
+            if let Some((winner, _)) = votes.iter().find(|(_, count)| count * 2 > total) {
+                filtered.stitches[r][c] = (*winner).clone();
+            }
+        }
+    }
+    filtered
+}
+
+// The in-bounds positions directly above, below, left and right of (`row`, `col`).
+fn neighbors(row: usize, col: usize, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    if row > 0 {
+        result.push((row - 1, col));
+    }
+    if col > 0 {
+        result.push((row, col - 1));
+    }
+    if col + 1 < cols {
+        result.push((row, col + 1));
+    }
+    if row + 1 < rows {
+        result.push((row + 1, col));
+    }
+    result
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_despeckle() {
+        let chart = chart!(
+            "*.....",
+            "...**.",
+            "..*.*.",
+            "..***.",
+            "......"
+        )?;
+
+        let (cleaned, changed) = clean_chart(&chart, &CleanOptions::despeckle(2))?;
+        let cleaned_str = chart_str!(
+            "......",
+            "...**.",
+            "..***.",
+            "..***.",
+            "......"
+        );
+        assert_eq!(cleaned.write_to_string()?, cleaned_str);
+        assert_eq!(changed, 2);
+    }
+
+    #[throws]
+    #[test]
+    fn test_clean_defaults_do_nothing() {
+        let chart = chart!(
+            "*.*",
+            ".*."
+        )?;
+
+        let (cleaned, changed) = clean_chart(&chart, &CleanOptions::default())?;
+        assert_eq!(cleaned.write_to_string()?, chart.write_to_string()?);
+        assert_eq!(changed, 0);
+    }
+
+    #[throws]
+    #[test]
+    fn test_majority() {
+        let chart = chart!(
+            "***.",
+            "*.*.",
+            "***.",
+            "...."
+        )?;
+
+        let options = CleanOptions {
+            majority: true,
+            ..CleanOptions::default()
+        };
+        let (cleaned, changed) = clean_chart(&chart, &options)?;
+        let cleaned_str = chart_str!(
+            "***.",
+            "***.",
+            "**..",
+            "...."
+        );
+        assert_eq!(cleaned.write_to_string()?, cleaned_str);
+        assert_eq!(changed, 2);
+    }
+}
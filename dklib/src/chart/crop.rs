@@ -0,0 +1,95 @@
+use crate::chart::Chart;
+use crate::units::{Cols, Height, Rows, Width};
+use crate::Error;
+use assure::assure;
+use fehler::throws;
+use std::convert::TryFrom;
+
+// Copy the `height` x `width` rectangle with its top-left corner at (`row`, `col`) out of `chart`.
+//
+// The entire rectangle must lie within `chart`.
+#[throws]
+pub fn crop_chart(chart: &Chart, row: Rows, col: Cols, height: Height, width: Width) -> Chart {
+    assure!(
+        height > Height::from(0u8),
+        Error::ZeroNotLegal { argname: "height" }
+    );
+    assure!(
+        width > Width::from(0u8),
+        Error::ZeroNotLegal { argname: "width" }
+    );
+
+    // Checking both corners ensures that the whole rectangle is inside the chart.
+    chart.range_check(row, col)?;
+    chart.range_check(
+        Rows::try_from(u32::from(row) + u32::from(height) - 1)?,
+        Cols::try_from(u32::from(col) + u32::from(width) - 1)?,
+    )?;
+
+    let mut cropped = Chart::new(width, height);
+    cropped.inherit_metadata(chart);
+    for crop_row in cropped.rows() {
+        for crop_col in cropped.cols() {
+            let stitch = chart.stitch(row + crop_row, col + crop_col)?.clone();
+            cropped.set_stitch(crop_row, crop_col, stitch)?;
+        }
+    }
+
+    cropped
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+
+    #[throws]
+    #[test]
+    fn test_crop() {
+        let chart = chart!(
+            "......",
+            ".*..*.",
+            "..**..",
+            ".*..*.",
+            "......"
+        )?;
+
+        let cropped = crop_chart(&chart, 1u8.into(), 1u8.into(), 3u8.into(), 4u8.into())?;
+        let cropped_str = chart_str!(
+            "*..*",
+            ".**.",
+            "*..*"
+        );
+        assert_eq!(cropped.write_to_string()?, cropped_str);
+
+        let corner = crop_chart(&chart, 4u8.into(), 5u8.into(), 1u8.into(), 1u8.into())?;
+        assert_eq!(corner.write_to_string()?, chart_str!("."));
+    }
+
+    #[throws]
+    #[test]
+    fn test_crop_keeps_metadata() {
+        let text = "DIRECTION left-to-right\nLEGEND\n○ yarn over\nCHART\n.○.\n○.○\n";
+        let chart = Chart::read(&mut BufReader::new(text.as_bytes()))?;
+
+        let cropped = crop_chart(&chart, 1u8.into(), 1u8.into(), 1u8.into(), 2u8.into())?;
+        assert_eq!(
+            cropped.write_to_string()?,
+            "DIRECTION left-to-right\nLEGEND\n○ yarn over\nCHART\n.○\n"
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_crop_out_of_range() {
+        let chart = chart!(
+            "...",
+            "...")?;
+
+        assert!(crop_chart(&chart, 2u8.into(), 0u8.into(), 1u8.into(), 1u8.into()).is_err());
+        assert!(crop_chart(&chart, 0u8.into(), 0u8.into(), 3u8.into(), 1u8.into()).is_err());
+        assert!(crop_chart(&chart, 0u8.into(), 1u8.into(), 1u8.into(), 3u8.into()).is_err());
+        assert!(crop_chart(&chart, 0u8.into(), 0u8.into(), 0u8.into(), 1u8.into()).is_err());
+    }
+}
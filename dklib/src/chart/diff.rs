@@ -0,0 +1,175 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows};
+use crate::Error;
+use fehler::throws;
+use std::cmp::max;
+
+/// A single position where two charts differ.
+/// A stitch is `None` if the position is outside of that chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    pub row: Rows,
+    pub col: Cols,
+    pub left: Option<Stitch>,
+    pub right: Option<Stitch>,
+}
+
+/// The result of comparing two charts with `Chart::diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartDiff {
+    pub left_size: (Rows, Cols),
+    pub right_size: (Rows, Cols),
+    pub differences: Vec<Difference>,
+}
+
+impl ChartDiff {
+    /// True if the charts are identical.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    pub fn same_size(&self) -> bool {
+        self.left_size == self.right_size
+    }
+
+    /// Copy `chart` onto a chart big enough to hold both of the compared charts, and then
+    /// replace every stitch that differs with `marker`.
+    #[throws]
+    pub fn mark(&self, chart: &Chart, marker: char) -> Chart {
+        let rows = max(self.left_size.0, self.right_size.0);
+        let cols = max(self.left_size.1, self.right_size.1);
+        let mut marked = Chart::new(cols, rows);
+        marked.inherit_metadata(chart);
+        for row in chart.rows() {
+            for col in chart.cols() {
+                marked.set_stitch(row, col, chart.stitch(row, col)?.clone())?;
+            }
+        }
+        for difference in &self.differences {
+            marked.set_stitch(difference.row, difference.col, Stitch::new(marker, None))?;
+        }
+        marked
+    }
+}
+
+// Compare `left` and `right` stitch by stitch, including colors.
+//
+// The charts are lined up at their top-left corners. If they are different sizes, then every
+// position that is in one chart but not the other is a difference.
+#[throws]
+pub fn diff_charts(left: &Chart, right: &Chart) -> ChartDiff {
+    let rows = max(left.rows(), right.rows());
+    let cols = max(left.cols(), right.cols());
+
+    let mut differences = Vec::new();
+    for row in rows {
+        for col in cols {
+            let left_stitch = stitch_if_inside(left, row, col)?;
+            let right_stitch = stitch_if_inside(right, row, col)?;
+            if left_stitch != right_stitch {
+                differences.push(Difference {
+                    row,
+                    col,
+                    left: left_stitch,
+                    right: right_stitch,
+                });
+            }
+        }
+    }
+
+    ChartDiff {
+        left_size: (left.rows(), left.cols()),
+        right_size: (right.rows(), right.cols()),
+        differences,
+    }
+}
+
+#[throws]
+fn stitch_if_inside(chart: &Chart, row: Rows, col: Cols) -> Option<Stitch> {
+    if row < chart.rows() && col < chart.cols() {
+        Some(chart.stitch(row, col)?.clone())
+    } else {
+        None
+    }
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use css_color_parser::Color;
+    use std::str::FromStr;
+
+    #[throws]
+    #[test]
+    fn test_diff_same() {
+        let chart = chart!(
+            "*..",
+            ".*."
+        )?;
+
+        let diff = diff_charts(&chart, &chart.clone())?;
+        assert!(diff.is_empty());
+        assert!(diff.same_size());
+    }
+
+    #[throws]
+    #[test]
+    fn test_diff() {
+        let left = chart!(
+            "*..",
+            ".*."
+        )?;
+        let right = chart!(
+            "*.*",
+            "..."
+        )?;
+
+        let diff = diff_charts(&left, &right)?;
+        let positions: Vec<(Rows, Cols)> =
+            diff.differences.iter().map(|d| (d.row, d.col)).collect();
+        assert_eq!(
+            positions,
+            vec![(0u8.into(), 2u8.into()), (1u8.into(), 1u8.into())]
+        );
+        assert_eq!(diff.differences[0].left, Some(Stitch::new('.', None)));
+        assert_eq!(diff.differences[0].right, Some(Stitch::new('*', None)));
+
+        let marked = diff.mark(&right, '#')?;
+        assert_eq!(marked.write_to_string()?, chart_str!("*.#", ".#."));
+    }
+
+    #[throws]
+    #[test]
+    fn test_diff_sizes() {
+        let left = chart!(
+            "**",
+            "**"
+        )?;
+        let right = chart!(
+            "***"
+        )?;
+
+        let diff = diff_charts(&left, &right)?;
+        assert!(!diff.same_size());
+        assert_eq!(diff.differences.len(), 3);
+        assert_eq!(diff.differences[0].left, None);
+        assert_eq!(diff.differences[1].right, None);
+
+        let marked = diff.mark(&right, '#')?;
+        assert_eq!(marked.write_to_string()?, chart_str!("**#", "## "));
+    }
+
+    #[throws]
+    #[test]
+    fn test_diff_colors() {
+        let left = chart!("**")?;
+        let mut right = left.clone();
+        let red = Stitch::new('*', Some(Color::from_str("red")?));
+        right.set_stitch(0u8.into(), 1u8.into(), red)?;
+
+        let diff = diff_charts(&left, &right)?;
+        assert_eq!(diff.differences.len(), 1);
+        assert_eq!(diff.differences[0].col, 1u8.into());
+    }
+}
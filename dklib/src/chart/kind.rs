@@ -0,0 +1,145 @@
+use crate::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// What a stitch actually is, as opposed to the symbol used to draw it.
+///
+/// Symbols get a kind from the chart's legend, where a description that names a kind
+/// (e.g. `╱ k2tog`) gives the symbol that kind.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StitchKind {
+    Knit,
+    Purl,
+    Slip,
+    YarnOver,
+    /// Knit two together, leaning right.
+    K2tog,
+    /// Slip, slip, knit, leaning left.
+    Ssk,
+    /// A placeholder for a square in the chart that has no stitch.
+    NoStitch,
+    /// A cable that crosses `width` stitches, with the front stitches leaning left.
+    CableLeft {
+        width: u8,
+    },
+    /// A cable that crosses `width` stitches, with the front stitches leaning right.
+    CableRight {
+        width: u8,
+    },
+    Bobble,
+}
+
+impl StitchKind {
+    /// The kind that this one becomes in a mirror image of the chart.
+    /// Stitches that don't lean either way are unchanged.
+    pub fn mirrored(self) -> StitchKind {
+        match self {
+            StitchKind::K2tog => StitchKind::Ssk,
+            StitchKind::Ssk => StitchKind::K2tog,
+            StitchKind::CableLeft { width } => StitchKind::CableRight { width },
+            StitchKind::CableRight { width } => StitchKind::CableLeft { width },
+            other => other,
+        }
+    }
+}
+
+impl Display for StitchKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StitchKind::Knit => write!(f, "knit"),
+            StitchKind::Purl => write!(f, "purl"),
+            StitchKind::Slip => write!(f, "slip"),
+            StitchKind::YarnOver => write!(f, "yarn over"),
+            StitchKind::K2tog => write!(f, "k2tog"),
+            StitchKind::Ssk => write!(f, "ssk"),
+            StitchKind::NoStitch => write!(f, "no stitch"),
+            StitchKind::CableLeft { width } => write!(f, "cable left {}", width),
+            StitchKind::CableRight { width } => write!(f, "cable right {}", width),
+            StitchKind::Bobble => write!(f, "bobble"),
+        }
+    }
+}
+
+impl FromStr for StitchKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let words: Vec<&str> = lower.split_whitespace().collect();
+        let kind = match words.as_slice() {
+            ["knit"] | ["k"] => StitchKind::Knit,
+            ["purl"] | ["p"] => StitchKind::Purl,
+            ["slip"] | ["sl"] => StitchKind::Slip,
+            ["yarn", "over"] | ["yo"] => StitchKind::YarnOver,
+            ["k2tog"] => StitchKind::K2tog,
+            ["ssk"] => StitchKind::Ssk,
+            ["no", "stitch"] => StitchKind::NoStitch,
+            ["bobble"] => StitchKind::Bobble,
+            // A cable needs at least one stitch on each side of the cross.
+            ["cable", "left", width] => match width.parse() {
+                Ok(width) if width >= 2 => StitchKind::CableLeft { width },
+                _ => return Err(unknown_kind(s)),
+            },
+            ["cable", "right", width] => match width.parse() {
+                Ok(width) if width >= 2 => StitchKind::CableRight { width },
+                _ => return Err(unknown_kind(s)),
+            },
+            _ => return Err(unknown_kind(s)),
+        };
+        Ok(kind)
+    }
+}
+
+fn unknown_kind(s: &str) -> Error {
+    Error::UnknownOption {
+        name: "stitch kind",
+        value: s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fehler::throws;
+
+    #[throws]
+    #[test]
+    fn test_from_str() {
+        assert_eq!(StitchKind::from_str("yo")?, StitchKind::YarnOver);
+        assert_eq!(StitchKind::from_str("Yarn Over")?, StitchKind::YarnOver);
+        assert_eq!(
+            StitchKind::from_str("cable left 4")?,
+            StitchKind::CableLeft { width: 4 }
+        );
+        assert!(StitchKind::from_str("cable left 1").is_err());
+        assert!(StitchKind::from_str("cable up 4").is_err());
+        assert!(StitchKind::from_str("yarn over twice").is_err());
+
+        let kinds = [
+            StitchKind::Knit,
+            StitchKind::Purl,
+            StitchKind::Slip,
+            StitchKind::YarnOver,
+            StitchKind::K2tog,
+            StitchKind::Ssk,
+            StitchKind::NoStitch,
+            StitchKind::CableLeft { width: 6 },
+            StitchKind::CableRight { width: 4 },
+            StitchKind::Bobble,
+        ];
+        for kind in &kinds {
+            assert_eq!(StitchKind::from_str(&kind.to_string())?, *kind);
+        }
+    }
+
+    #[test]
+    fn test_mirrored() {
+        assert_eq!(StitchKind::K2tog.mirrored(), StitchKind::Ssk);
+        assert_eq!(StitchKind::Ssk.mirrored(), StitchKind::K2tog);
+        assert_eq!(
+            StitchKind::CableLeft { width: 4 }.mirrored(),
+            StitchKind::CableRight { width: 4 }
+        );
+        assert_eq!(StitchKind::Bobble.mirrored(), StitchKind::Bobble);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::chart::StitchKind;
+
+/// Says what a stitch symbol means, for symbols that aren't obvious from the chart.
+///
+/// Legend entries are stored in the header of a chart file, between a `LEGEND` line and the
+/// `CHART` line, one per line as the symbol, a space, and the description, e.g. `○ yarn over`.
+/// If the description names a `StitchKind`, the symbol is that kind of stitch.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegendEntry {
+    pub symbol: char,
+    pub description: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: Option<StitchKind>,
+}
+
+impl LegendEntry {
+    pub fn new(symbol: char, description: impl Into<String>) -> LegendEntry {
+        let description = description.into();
+        LegendEntry {
+            symbol,
+            kind: description.parse().ok(),
+            description,
+        }
+    }
+
+    /// An entry whose description is the name of `kind`.
+    pub fn for_kind(symbol: char, kind: StitchKind) -> LegendEntry {
+        LegendEntry {
+            symbol,
+            description: kind.to_string(),
+            kind: Some(kind),
+        }
+    }
+}
+
+// Parse a legend line, e.g. "○ yarn over". The symbol may itself be a space, as in "  purl".
+pub(crate) fn parse_legend_entry(line: &str) -> Option<LegendEntry> {
+    let mut chars = line.chars();
+    let symbol = chars.next()?;
+    let rest = chars.as_str();
+    let description = rest.trim();
+    if !rest.starts_with(' ') || description.is_empty() {
+        return None;
+    }
+    Some(LegendEntry::new(symbol, description))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_legend_entry() {
+        assert_eq!(
+            parse_legend_entry("○ yarn over"),
+            Some(LegendEntry::new('○', "yarn over"))
+        );
+        assert_eq!(
+            parse_legend_entry("  purl"),
+            Some(LegendEntry::new(' ', "purl"))
+        );
+        assert_eq!(
+            parse_legend_entry("╱ k2tog").and_then(|entry| entry.kind),
+            Some(StitchKind::K2tog)
+        );
+        assert_eq!(parse_legend_entry("○"), None);
+        assert_eq!(parse_legend_entry("○x yarn over"), None);
+        assert_eq!(parse_legend_entry(""), None);
+    }
+}
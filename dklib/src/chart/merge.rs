@@ -36,6 +36,8 @@ pub fn merge_charts(left: &Chart, right: &Chart) -> Chart {
     let color_one = Color::from_str("lightblue")?;
     let color_two = Color::from_str("goldenrod")?;
     let mut merged = Chart::new(left.cols() + right.cols(), left.rows());
+    merged.inherit_metadata(left);
+    merged.inherit_metadata(right);
 
     for row in merged.rows() {
         for col in merged.cols() {
@@ -0,0 +1,259 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows};
+use crate::{Error, Result};
+use fehler::throws;
+use std::convert::TryFrom;
+
+// Morphological operations on the shapes in a chart. A shape is made of the non-empty stitches,
+// and neighborhoods are squares, so a radius of 1 includes the diagonals. Anything outside of
+// the chart counts as empty.
+
+// Thicken shapes by filling every empty stitch within `radius` of a shape.
+// The new stitches are copies of the nearest stitch in the shape.
+#[throws]
+pub fn dilate_chart(chart: &Chart, radius: u16) -> Chart {
+    let radius = i64::from(radius);
+    let mut dilated = chart.clone();
+    for row in chart.rows() {
+        for col in chart.cols() {
+            if !chart.stitch(row, col)?.is_empty() {
+                continue;
+            }
+            if let Some(stitch) = nearest_filled(chart, row, col, radius)? {
+                dilated.set_stitch(row, col, stitch.clone())?;
+            }
+        }
+    }
+    dilated
+}
+
+// Thin shapes by replacing every stitch within `radius` of an empty stitch with `background`.
+#[throws]
+pub fn erode_chart(chart: &Chart, radius: u16, background: char) -> Chart {
+    let radius = i64::from(radius);
+    let mut eroded = chart.clone();
+    for row in chart.rows() {
+        for col in chart.cols() {
+            if chart.stitch(row, col)?.is_empty() {
+                continue;
+            }
+            let (r, c) = (i64::from(u32::from(row)), i64::from(u32::from(col)));
+            let mut touches_empty = false;
+            for dr in -radius..=radius {
+                for dc in -radius..=radius {
+                    touches_empty = touches_empty || !is_filled(chart, r + dr, c + dc)?;
+                }
+            }
+            if touches_empty {
+                eroded.set_stitch(row, col, Stitch::new(background, None))?;
+            }
+        }
+    }
+    eroded
+}
+
+// Keep only the stitches on the boundary of each shape: those with an empty stitch directly
+// above, below, left or right of them. Everything else becomes `background`.
+#[throws]
+pub fn outline_chart(chart: &Chart, background: char) -> Chart {
+    let mut outlined = Chart::new(chart.cols(), chart.rows());
+    outlined.inherit_metadata(chart);
+    for row in chart.rows() {
+        for col in chart.cols() {
+            let stitch = chart.stitch(row, col)?;
+            let (r, c) = (i64::from(u32::from(row)), i64::from(u32::from(col)));
+            let on_boundary = !stitch.is_empty()
+                && (!is_filled(chart, r - 1, c)?
+                    || !is_filled(chart, r + 1, c)?
+                    || !is_filled(chart, r, c - 1)?
+                    || !is_filled(chart, r, c + 1)?);
+            let new_stitch = if on_boundary {
+                stitch.clone()
+            } else {
+                Stitch::new(background, None)
+            };
+            outlined.set_stitch(row, col, new_stitch)?;
+        }
+    }
+    outlined
+}
+
+// Surround the shapes in `chart` with a ring of `halo` stitches `radius` wide.
+//
+// The result is `radius` stitches larger on every side, to make room for the ring. Stitches
+// that are neither in a shape nor in its halo become `outside`, so that they can be skipped
+// when stamping the result onto another chart.
+#[throws]
+pub fn halo_chart(chart: &Chart, radius: u16, halo: char, outside: char) -> Chart {
+    let offset = i64::from(radius);
+    let margin = 2 * u32::from(radius);
+    let mut haloed = Chart::new(
+        Cols::try_from(u32::from(chart.cols()) + margin)?,
+        Rows::try_from(u32::from(chart.rows()) + margin)?,
+    );
+    haloed.inherit_metadata(chart);
+    for row in haloed.rows() {
+        for col in haloed.cols() {
+            let (r, c) = (
+                i64::from(u32::from(row)) - offset,
+                i64::from(u32::from(col)) - offset,
+            );
+            let stitch = if is_filled(chart, r, c)? {
+                chart
+                    .stitch(Rows::try_from(r as u32)?, Cols::try_from(c as u32)?)?
+                    .clone()
+            } else if nearest_filled_at(chart, r, c, offset)?.is_some() {
+                Stitch::new(halo, None)
+            } else {
+                Stitch::new(outside, None)
+            };
+            haloed.set_stitch(row, col, stitch)?;
+        }
+    }
+    haloed
+}
+
+// Is there a non-empty stitch at (`row`, `col`)? Out-of-bounds stitches are empty.
+fn is_filled(chart: &Chart, row: i64, col: i64) -> Result<bool> {
+    if row < 0
+        || col < 0
+        || row >= i64::from(u32::from(chart.rows()))
+        || col >= i64::from(u32::from(chart.cols()))
+    {
+        return Ok(false);
+    }
+    Ok(!chart
+        .stitch(Rows::try_from(row as u32)?, Cols::try_from(col as u32)?)?
+        .is_empty())
+}
+
+fn nearest_filled(chart: &Chart, row: Rows, col: Cols, radius: i64) -> Result<Option<&Stitch>> {
+    nearest_filled_at(
+        chart,
+        i64::from(u32::from(row)),
+        i64::from(u32::from(col)),
+        radius,
+    )
+}
+
+// Find the closest non-empty stitch within `radius` of (`row`, `col`), searching outward
+// in square rings. Within a ring, the first stitch in reading order wins.
+fn nearest_filled_at(chart: &Chart, row: i64, col: i64, radius: i64) -> Result<Option<&Stitch>> {
+    for distance in 1..=radius {
+        for r in row - distance..=row + distance {
+            for c in col - distance..=col + distance {
+                let on_ring = (r - row).abs() == distance || (c - col).abs() == distance;
+                if on_ring && is_filled(chart, r, c)? {
+                    let stitch =
+                        chart.stitch(Rows::try_from(r as u32)?, Cols::try_from(c as u32)?)?;
+                    return Ok(Some(stitch));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_dilate() {
+        let chart = chart!(
+            ".....",
+            ".....",
+            "..*..",
+            ".....",
+            "....."
+        )?;
+
+        let dilated = dilate_chart(&chart, 1)?;
+        let dilated_str = chart_str!(
+            ".....",
+            ".***.",
+            ".***.",
+            ".***.",
+            "....."
+        );
+        assert_eq!(dilated.write_to_string()?, dilated_str);
+
+        let unchanged = dilate_chart(&chart, 0)?;
+        assert_eq!(unchanged.write_to_string()?, chart.write_to_string()?);
+    }
+
+    #[throws]
+    #[test]
+    fn test_dilate_copies_nearest() {
+        let chart = chart!(
+            "x...o"
+        )?;
+
+        let dilated = dilate_chart(&chart, 2)?;
+        assert_eq!(dilated.write_to_string()?, chart_str!("xxxoo"));
+    }
+
+    #[throws]
+    #[test]
+    fn test_erode() {
+        let chart = chart!(
+            ".....",
+            ".***.",
+            ".***.",
+            ".***.",
+            "....."
+        )?;
+
+        let eroded = erode_chart(&chart, 1, '.')?;
+        let eroded_str = chart_str!(
+            ".....",
+            ".....",
+            "..*..",
+            ".....",
+            "....."
+        );
+        assert_eq!(eroded.write_to_string()?, eroded_str);
+    }
+
+    #[throws]
+    #[test]
+    fn test_outline() {
+        let chart = chart!(
+            "*****",
+            "*****",
+            "*****",
+            "....."
+        )?;
+
+        let outlined = outline_chart(&chart, '.')?;
+        let outlined_str = chart_str!(
+            "*****",
+            "*...*",
+            "*****",
+            "....."
+        );
+        assert_eq!(outlined.write_to_string()?, outlined_str);
+    }
+
+    #[throws]
+    #[test]
+    fn test_halo() {
+        let chart = chart!(
+            "*..",
+            "..."
+        )?;
+
+        let haloed = halo_chart(&chart, 1, '.', '~')?;
+        let haloed_str = chart_str!(
+            "...~~",
+            ".*.~~",
+            "...~~",
+            "~~~~~"
+        );
+        assert_eq!(haloed.write_to_string()?, haloed_str);
+
+        assert!(halo_chart(&chart, u16::MAX, '.', '~').is_err());
+    }
+}
@@ -5,6 +5,7 @@ use fehler::throws;
 #[throws]
 pub fn pad_chart(chart: &Chart, ch: char) -> Chart {
     let mut padded = Chart::new(chart.cols() + 2, chart.rows() + 2);
+    padded.inherit_metadata(chart);
 
     // Inefficient, but easy.
     for row in padded.rows() {
@@ -0,0 +1,127 @@
+use crate::{Error, Location, ParseError};
+use fehler::throws;
+use std::cmp::min;
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// How to handle rows of different lengths when reading a chart.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadMode {
+    /// Pad short rows with '.' to the length of the longest row.
+    Lenient,
+    /// Pad short rows like `Lenient`, but also return a warning for each mismatched row.
+    Warn,
+    /// Fail on the first row with a different length than the first row.
+    Strict,
+}
+
+/// Settings for `Chart::read_with`. The default settings are lenient.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReadOptions {
+    pub mode: ReadMode,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            mode: ReadMode::Lenient,
+        }
+    }
+}
+
+/// A problem found while reading a chart that was not bad enough to stop reading.
+#[derive(Debug)]
+pub struct ReadWarning {
+    pub location: Location,
+    pub kind: ParseError,
+}
+
+impl Display for ReadWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+// Check the length of a row against the length of the first row, the way `options` says to.
+// `location` gives the location of a column (starting at 1) in the row.
+#[throws]
+pub(crate) fn check_row_length(
+    options: &ReadOptions,
+    expected: usize,
+    found: usize,
+    location: impl FnOnce(usize) -> Location,
+    warnings: &mut Vec<ReadWarning>,
+) {
+    if found == expected || options.mode == ReadMode::Lenient {
+        return;
+    }
+    // Point at the first stitch that is missing or extra.
+    let location = location(min(found, expected) + 1);
+    let kind = ParseError::RaggedRow { expected, found };
+    if options.mode == ReadMode::Strict {
+        Err(Error::Parse { location, kind })?
+    } else {
+        warnings.push(ReadWarning { location, kind });
+    }
+}
+
+// Reads a chart file one line at a time, keeping track of the position for error messages.
+pub(crate) struct LineReader<'a, R: BufRead> {
+    rdr: &'a mut R,
+    file: Option<PathBuf>,
+    line_number: usize,
+    line: String,
+}
+
+impl<'a, R: BufRead> LineReader<'a, R> {
+    pub(crate) fn new(rdr: &'a mut R, file: Option<&Path>) -> LineReader<'a, R> {
+        LineReader {
+            rdr,
+            file: file.map(Path::to_path_buf),
+            line_number: 0,
+            line: String::new(),
+        }
+    }
+
+    // Advance to the next line. Returns false at the end of the file.
+    #[throws]
+    pub(crate) fn next_line(&mut self) -> bool {
+        self.line.clear();
+        self.line_number += 1;
+        let size = match self.rdr.read_line(&mut self.line) {
+            Ok(size) => size,
+            Err(err) if err.kind() == ErrorKind::InvalidData => {
+                Err(self.error(1, ParseError::InvalidUtf8))?
+            }
+            Err(err) => Err(err)?,
+        };
+        if self.line.ends_with('\n') {
+            self.line.pop();
+        }
+        size > 0
+    }
+
+    // The current line, without its line ending.
+    pub(crate) fn line(&self) -> &str {
+        &self.line
+    }
+
+    // The location of the character at `column` (starting at 1) in the current line.
+    pub(crate) fn location(&self, column: usize) -> Location {
+        Location {
+            file: self.file.clone(),
+            line: self.line_number,
+            column,
+            text: self.line.clone(),
+        }
+    }
+
+    // An error for the character at `column` (starting at 1) in the current line.
+    pub(crate) fn error(&self, column: usize, kind: ParseError) -> Error {
+        Error::Parse {
+            location: self.location(column),
+            kind,
+        }
+    }
+}
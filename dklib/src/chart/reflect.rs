@@ -1,21 +1,92 @@
-use crate::chart::Chart;
+use crate::chart::{Chart, Stitch};
 use crate::Error;
 use fehler::throws;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
+/// The direction that the rows of a chart are read in. Charts are normally read from right to
+/// left, the way a right-handed knitter works across the right side of the fabric.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadingDirection {
+    RightToLeft,
+    LeftToRight,
+}
+
+impl ReadingDirection {
+    pub fn reversed(self) -> ReadingDirection {
+        match self {
+            ReadingDirection::RightToLeft => ReadingDirection::LeftToRight,
+            ReadingDirection::LeftToRight => ReadingDirection::RightToLeft,
+        }
+    }
+}
+
+impl Display for ReadingDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadingDirection::RightToLeft => write!(f, "right-to-left"),
+            ReadingDirection::LeftToRight => write!(f, "left-to-right"),
+        }
+    }
+}
+
+impl FromStr for ReadingDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "right-to-left" => Ok(ReadingDirection::RightToLeft),
+            "left-to-right" => Ok(ReadingDirection::LeftToRight),
+            _ => Err(Error::UnknownOption {
+                name: "reading direction",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+// Mirror `chart` left to right.
+//
+// Stitches that lean are swapped for the legend's symbol for their mirror image, so a k2tog
+// becomes an ssk and a left cable becomes a right cable. Symbols whose mirror image isn't in
+// the legend are left alone.
 #[throws]
 pub fn reflect_chart(chart: &Chart) -> Chart {
     let mut reflected = Chart::new(chart.cols(), chart.rows());
+    reflected.inherit_metadata(chart);
 
     for row in chart.rows() {
         for col in chart.cols() {
             let stitch = chart.stitch(row, col)?;
-            reflected.set_stitch(row, reflected.cols() - col - 1, stitch.clone())?;
+            let symbol = mirrored_symbol(chart, stitch.symbol());
+            reflected.set_stitch(
+                row,
+                reflected.cols() - col - 1,
+                Stitch::new(symbol, stitch.color()),
+            )?;
         }
     }
 
     reflected
 }
 
+// Mirror `chart` for a knitter who works in the opposite direction, so that they can read the
+// rows in their usual order. This is `reflect_chart`, including swapping leaning stitches,
+// since a knitter working the other way makes every decrease and cable lean the other way.
+#[throws]
+pub fn reverse_reading_direction(chart: &Chart) -> Chart {
+    let mut reversed = reflect_chart(chart)?;
+    reversed.set_reading_direction(chart.reading_direction().reversed());
+    reversed
+}
+
+fn mirrored_symbol(chart: &Chart, symbol: char) -> char {
+    chart
+        .kind(symbol)
+        .and_then(|kind| chart.symbol_for(kind.mirrored()))
+        .unwrap_or(symbol)
+}
+
 #[rustfmt::skip::macros(chart, chart_str)]
 #[cfg(test)]
 mod test {
@@ -35,4 +106,55 @@ mod test {
             "**.");
         assert_eq!(reflected.write_to_string()?, reflected_str);
     }
+
+    #[throws]
+    #[test]
+    fn test_reflect_directional() {
+        let text = "LEGEND\n╱ k2tog\n╲ ssk\n< cable left 4\n> cable right 4\n○ yo\n\
+                    CHART\n╱.○.\n<<<<\n";
+        let chart = Chart::read(&mut std::io::BufReader::new(text.as_bytes()))?;
+        let reflected = reflect_chart(&chart)?;
+        assert_eq!(reflected.legend(), chart.legend());
+
+        let symbols: Vec<String> = reflected
+            .rows()
+            .into_iter()
+            .map(|row| {
+                reflected
+                    .cols()
+                    .into_iter()
+                    .map(|col| reflected.stitch(row, col).unwrap().symbol())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(symbols, vec![".○.╲", ">>>>"]);
+    }
+
+    #[throws]
+    #[test]
+    fn test_reverse_reading_direction() {
+        let chart = chart!(
+            "*..",
+            "**.")?;
+        assert_eq!(chart.reading_direction(), ReadingDirection::RightToLeft);
+
+        let reversed = reverse_reading_direction(&chart)?;
+        assert_eq!(reversed.reading_direction(), ReadingDirection::LeftToRight);
+        let reversed_str = format!(
+            "DIRECTION left-to-right\n{}",
+            chart_str!(
+                "..*",
+                ".**")
+        );
+        assert_eq!(reversed.write_to_string()?, reversed_str);
+
+        // Reflecting keeps the direction, and reversing again goes back.
+        let reread = Chart::read(&mut std::io::BufReader::new(reversed_str.as_bytes()))?;
+        assert_eq!(
+            reread.reflect()?.reading_direction(),
+            ReadingDirection::LeftToRight
+        );
+        let back = reverse_reading_direction(&reread)?;
+        assert_eq!(back.reading_direction(), ReadingDirection::RightToLeft);
+    }
 }
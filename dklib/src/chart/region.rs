@@ -0,0 +1,232 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Height, Rows, Width};
+use crate::Error;
+use css_color_parser::Color;
+use fehler::throws;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Which stitches count as touching when finding regions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    /// Stitches touch if they share an edge.
+    Four,
+    /// Stitches touch if they share an edge or a corner.
+    Eight,
+}
+
+impl FromStr for Connectivity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Connectivity::Four),
+            "8" => Ok(Connectivity::Eight),
+            _ => Err(Error::UnknownOption {
+                name: "connectivity",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// A connected group of identical stitches.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub symbol: char,
+    pub color: Option<Color>,
+    /// The number of stitches in the region.
+    pub size: u32,
+
+    /// The bounding box of the region.
+    pub top: Rows,
+    pub left: Cols,
+    pub height: Height,
+    pub width: Width,
+}
+
+// Replace the region of identical stitches containing (`row`, `col`) with `stitch`.
+#[throws]
+pub fn flood_fill(
+    chart: &Chart,
+    row: Rows,
+    col: Cols,
+    stitch: Stitch,
+    connectivity: Connectivity,
+) -> Chart {
+    chart.range_check(row, col)?;
+
+    let mut filled = chart.clone();
+    let mut seen = vec![vec![false; usize::from(chart.cols)]; usize::from(chart.rows)];
+    for (r, c) in collect_region(chart, row, col, connectivity, &mut seen) {
+        filled.stitches[r][c] = stitch.clone();
+    }
+    filled
+}
+
+// Find all of the regions of identical stitches in `chart`, in the order that their
+// top-left-most stitches appear.
+#[throws]
+pub fn label_regions(chart: &Chart, connectivity: Connectivity) -> Vec<Region> {
+    let mut seen = vec![vec![false; usize::from(chart.cols)]; usize::from(chart.rows)];
+    let mut regions = Vec::new();
+    for row in chart.rows() {
+        for col in chart.cols() {
+            if seen[usize::from(row)][usize::from(col)] {
+                continue;
+            }
+
+            let stitch = chart.stitch(row, col)?;
+            let members = collect_region(chart, row, col, connectivity, &mut seen);
+            let top = members.iter().map(|(r, _)| *r).min().unwrap_or_default();
+            let bottom = members.iter().map(|(r, _)| *r).max().unwrap_or_default();
+            let left = members.iter().map(|(_, c)| *c).min().unwrap_or_default();
+            let right = members.iter().map(|(_, c)| *c).max().unwrap_or_default();
+            regions.push(Region {
+                symbol: stitch.symbol(),
+                color: stitch.color(),
+                size: u32::try_from(members.len())?,
+                top: Rows::try_from(top)?,
+                left: Cols::try_from(left)?,
+                height: Height::try_from(bottom - top + 1)?,
+                width: Width::try_from(right - left + 1)?,
+            });
+        }
+    }
+    regions
+}
+
+// Mark and return the (row, col) of every stitch connected to (`row`, `col`) that is identical
+// to it. `seen` must be the same size as `chart`.
+pub(crate) fn collect_region(
+    chart: &Chart,
+    row: Rows,
+    col: Cols,
+    connectivity: Connectivity,
+    seen: &mut [Vec<bool>],
+) -> Vec<(usize, usize)> {
+    let rows = usize::from(chart.rows) as isize;
+    let cols = usize::from(chart.cols) as isize;
+    let (row, col) = (usize::from(row), usize::from(col));
+    let target = &chart.stitches[row][col];
+
+    let neighbors: &[(isize, isize)] = match connectivity {
+        Connectivity::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        Connectivity::Eight => &[
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ],
+    };
+
+    let mut members = Vec::new();
+    let mut pending = vec![(row, col)];
+    seen[row][col] = true;
+    while let Some((r, c)) = pending.pop() {
+        members.push((r, c));
+        for (dr, dc) in neighbors {
+            let (nr, nc) = (r as isize + dr, c as isize + dc);
+            if nr < 0 || nc < 0 || nr >= rows || nc >= cols {
+                continue;
+            }
+            let (nr, nc) = (nr as usize, nc as usize);
+            if !seen[nr][nc] && chart.stitches[nr][nc] == *target {
+                seen[nr][nc] = true;
+                pending.push((nr, nc));
+            }
+        }
+    }
+    members
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_flood_fill() {
+        let chart = chart!(
+            "**..",
+            "*..*",
+            ".*.*"
+        )?;
+
+        let filled = flood_fill(
+            &chart,
+            0u8.into(),
+            2u8.into(),
+            Stitch::new('x', None),
+            Connectivity::Four,
+        )?;
+        let filled_str = chart_str!(
+            "**xx",
+            "*xx*",
+            ".*x*"
+        );
+        assert_eq!(filled.write_to_string()?, filled_str);
+
+        // With diagonals, the '*' at the bottom joins the group in the corner.
+        let filled = flood_fill(
+            &chart,
+            0u8.into(),
+            0u8.into(),
+            Stitch::new('o', None),
+            Connectivity::Eight,
+        )?;
+        let filled_str = chart_str!(
+            "oo..",
+            "o..*",
+            ".o.*"
+        );
+        assert_eq!(filled.write_to_string()?, filled_str);
+
+        assert!(flood_fill(
+            &chart,
+            3u8.into(),
+            0u8.into(),
+            Stitch::default(),
+            Connectivity::Four
+        )
+        .is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_label_regions() {
+        let chart = chart!(
+            "**..",
+            "*..*",
+            ".*.*"
+        )?;
+
+        let regions = label_regions(&chart, Connectivity::Four)?;
+        let summary: Vec<(char, u32)> = regions.iter().map(|r| (r.symbol, r.size)).collect();
+        assert_eq!(
+            summary,
+            vec![('*', 3), ('.', 5), ('*', 2), ('.', 1), ('*', 1)]
+        );
+
+        let dots = &regions[1];
+        assert_eq!((dots.top, dots.left), (0u8.into(), 1u8.into()));
+        assert_eq!((dots.height, dots.width), (3u8.into(), 3u8.into()));
+
+        let regions = label_regions(&chart, Connectivity::Eight)?;
+        let summary: Vec<(char, u32)> = regions.iter().map(|r| (r.symbol, r.size)).collect();
+        assert_eq!(summary, vec![('*', 4), ('.', 6), ('*', 2)]);
+    }
+
+    #[throws]
+    #[test]
+    fn test_connectivity_from_str() {
+        assert_eq!(Connectivity::from_str("4")?, Connectivity::Four);
+        assert_eq!(Connectivity::from_str("8")?, Connectivity::Eight);
+        assert!(Connectivity::from_str("6").is_err());
+    }
+}
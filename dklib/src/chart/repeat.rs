@@ -1,14 +1,34 @@
 use crate::chart::Chart;
+use crate::units::{Cols, Height, Rows, Width};
 use crate::Error;
 use assure::assure;
 use fehler::throws;
+use std::convert::TryFrom;
+
+/// The number of whole and clipped repeats along one side of a filled chart.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RepeatCount {
+    pub full: u32,
+    pub partial: u32,
+}
+
+/// Describes how a chart was tiled by `repeat_to_fill`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FillReport {
+    pub horiz: RepeatCount,
+    pub vert: RepeatCount,
+}
 
 #[throws]
 pub fn repeat_chart(chart: &Chart, h: u8, v: u8) -> Chart {
     assure!(h > 0, Error::ZeroNotLegal { argname: "h" });
     assure!(v > 0, Error::ZeroNotLegal { argname: "v" });
 
-    let mut repeated = Chart::new(chart.cols() * h, chart.rows() * v);
+    let mut repeated = Chart::new(
+        Cols::try_from(u32::from(chart.cols()) * u32::from(h))?,
+        Rows::try_from(u32::from(chart.rows()) * u32::from(v))?,
+    );
+    repeated.inherit_metadata(chart);
 
     // TODO: reimplement this using "stamp"
     for h_repeat in 0..h {
@@ -30,6 +50,67 @@ pub fn repeat_chart(chart: &Chart, h: u8, v: u8) -> Chart {
     repeated
 }
 
+// Tile `chart` to exactly fill `width` x `height`, clipping any repeats that don't fit.
+//
+// If `centered` is set, the whole repeats are centered and the leftover stitches are split
+// between both edges, so that the partial repeats on either side are (nearly) the same size.
+#[throws]
+pub fn repeat_to_fill(
+    chart: &Chart,
+    width: Width,
+    height: Height,
+    centered: bool,
+) -> (Chart, FillReport) {
+    assure!(
+        width > Width::from(0u8),
+        Error::ZeroNotLegal { argname: "width" }
+    );
+    assure!(
+        height > Height::from(0u8),
+        Error::ZeroNotLegal { argname: "height" }
+    );
+    assure!(
+        chart.cols() > Cols::from(0u8) && chart.rows() > Rows::from(0u8),
+        Error::EmptyChart {
+            msg: "Cannot repeat"
+        }
+    );
+
+    let (col_offset, horiz) = fill_offset(chart.cols().into(), width.into(), centered);
+    let (row_offset, vert) = fill_offset(chart.rows().into(), height.into(), centered);
+
+    let mut filled = Chart::new(width, height);
+    filled.inherit_metadata(chart);
+    for row in filled.rows() {
+        for col in filled.cols() {
+            let inner_row =
+                Rows::try_from((u32::from(row) + row_offset) % u32::from(chart.rows()))?;
+            let inner_col =
+                Cols::try_from((u32::from(col) + col_offset) % u32::from(chart.cols()))?;
+            filled.set_stitch(row, col, chart.stitch(inner_row, inner_col)?.clone())?;
+        }
+    }
+
+    (filled, FillReport { horiz, vert })
+}
+
+// Returns the offset into the repeat of the first stitch, and the repeat counts,
+// for tiling a repeat of size `repeat` into `target` stitches.
+fn fill_offset(repeat: u32, target: u32, centered: bool) -> (u32, RepeatCount) {
+    let full = target / repeat;
+    let remainder = target % repeat;
+    if centered {
+        let leading = remainder / 2;
+        let trailing = remainder - leading;
+        let partial = u32::from(leading > 0) + u32::from(trailing > 0);
+        ((repeat - leading) % repeat, RepeatCount { full, partial })
+    } else {
+        let partial = u32::from(remainder > 0);
+        (0, RepeatCount { full, partial })
+    }
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
 #[cfg(test)]
 mod test {
     use super::*;
@@ -76,4 +157,57 @@ mod test {
         );
         assert_eq!(both.write_to_string()?, both_str);
     }
+
+    #[throws]
+    #[test]
+    fn test_repeat_to_fill() {
+        let chart = chart!(
+            "*..",
+            ".*.")?;
+
+        let (filled, report) = repeat_to_fill(&chart, 8u8.into(), 3u8.into(), false)?;
+        let filled_str = chart_str!(
+            "*..*..*.",
+            ".*..*..*",
+            "*..*..*.");
+        assert_eq!(filled.write_to_string()?, filled_str);
+        assert_eq!((report.horiz.full, report.horiz.partial), (2, 1));
+        assert_eq!((report.vert.full, report.vert.partial), (1, 1));
+
+        let (exact, report) = repeat_to_fill(&chart, 6u8.into(), 2u8.into(), false)?;
+        assert_eq!(
+            exact.write_to_string()?,
+            repeat_chart(&chart, 2, 1)?.write_to_string()?
+        );
+        assert_eq!((report.horiz.full, report.horiz.partial), (2, 0));
+        assert_eq!((report.vert.full, report.vert.partial), (1, 0));
+    }
+
+    #[throws]
+    #[test]
+    fn test_repeat_to_fill_centered() {
+        let chart = chart!(
+            "abcd")?;
+
+        let (filled, report) = repeat_to_fill(&chart, 10u8.into(), 1u8.into(), true)?;
+        assert_eq!(filled.write_to_string()?, chart_str!("dabcdabcda"));
+        assert_eq!((report.horiz.full, report.horiz.partial), (2, 2));
+
+        let (filled, report) = repeat_to_fill(&chart, 7u8.into(), 1u8.into(), true)?;
+        assert_eq!(filled.write_to_string()?, chart_str!("dabcdab"));
+        assert_eq!((report.horiz.full, report.horiz.partial), (1, 2));
+    }
+
+    #[test]
+    fn test_repeat_too_big() {
+        let chart = Chart::new(300u16, 1u8);
+        assert!(repeat_chart(&chart, 255, 1).is_err());
+    }
+
+    #[test]
+    fn test_repeat_to_fill_zero() {
+        let chart = chart!("*.").unwrap();
+        assert!(repeat_to_fill(&chart, 0u8.into(), 1u8.into(), false).is_err());
+        assert!(repeat_to_fill(&chart, 1u8.into(), 0u8.into(), false).is_err());
+    }
 }
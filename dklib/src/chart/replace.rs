@@ -0,0 +1,167 @@
+use crate::chart::{Chart, Stitch};
+use crate::Error;
+use assure::assure;
+use fehler::throws;
+
+// Produce the negative of `chart`.
+//
+// For colored charts (like those made by `merge_charts`), the two colors are swapped and the
+// symbols are left alone. Otherwise, purls ('*') become knits ('.') and knits become purls.
+// Any other symbols are left alone, so inverting twice gives back the original chart.
+//
+// A chart with only one color has nothing to swap it with, so it is an error.
+#[throws]
+pub fn invert_chart(chart: &Chart) -> Chart {
+    let mut colors = Vec::new();
+    for row in &chart.stitches {
+        for color in row.iter().filter_map(|stitch| stitch.color()) {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    assure!(
+        colors.len() <= 2,
+        Error::TooManyColors {
+            msg: "Cannot invert",
+            count: colors.len()
+        }
+    );
+    assure!(
+        colors.len() != 1,
+        Error::OneColor {
+            msg: "Cannot invert"
+        }
+    );
+
+    let mut inverted = chart.clone();
+    for row in &mut inverted.stitches {
+        for stitch in row {
+            if colors.is_empty() {
+                stitch.symbol = match stitch.symbol {
+                    '*' => '.',
+                    '.' => '*',
+                    other => other,
+                };
+            } else {
+                stitch.color = match stitch.color {
+                    Some(color) if color == colors[0] => Some(colors[1]),
+                    Some(color) if color == colors[1] => Some(colors[0]),
+                    other => other,
+                };
+            }
+        }
+    }
+
+    inverted
+}
+
+// Replace symbols in `chart` according to `mapping`, which is a list of (from, to) pairs.
+// All of the replacements happen at once, so `[('*', '.'), ('.', '*')]` swaps the symbols.
+#[throws]
+pub fn replace_symbols(chart: &Chart, mapping: &[(char, char)]) -> Chart {
+    let mut replaced = chart.clone();
+    for row in &mut replaced.stitches {
+        for stitch in row {
+            if let Some((_, to)) = mapping.iter().find(|(from, _)| *from == stitch.symbol()) {
+                *stitch = Stitch::new(*to, stitch.color());
+            }
+        }
+    }
+    replaced
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use css_color_parser::Color;
+    use std::str::FromStr;
+
+    #[throws]
+    #[test]
+    fn test_invert() {
+        let chart = chart!(
+            "*. ",
+            "x*.")?;
+
+        let inverted = invert_chart(&chart)?;
+        assert_eq!(inverted.write_to_string()?, chart_str!(".* ", "x.*"));
+        assert_eq!(
+            invert_chart(&inverted)?.write_to_string()?,
+            chart.write_to_string()?
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_invert_colors() {
+        let chart = chart!(
+            "*...",
+            ".**.")?;
+        let merged = chart.merge_with(&chart)?;
+
+        let inverted = invert_chart(&merged)?;
+        let symbols = |chart: &Chart| -> Vec<char> {
+            chart
+                .stitches
+                .iter()
+                .flatten()
+                .map(|s| s.symbol())
+                .collect()
+        };
+        assert_eq!(symbols(&inverted), symbols(&merged));
+
+        let blue = Color::from_str("lightblue")?;
+        let gold = Color::from_str("goldenrod")?;
+        for row in merged.rows() {
+            for col in merged.cols() {
+                let expected = if merged.stitch(row, col)?.color() == Some(blue) {
+                    gold
+                } else {
+                    blue
+                };
+                assert_eq!(inverted.stitch(row, col)?.color(), Some(expected));
+            }
+        }
+    }
+
+    #[throws]
+    #[test]
+    fn test_invert_too_many_colors() {
+        let mut chart = chart!("***")?;
+        for (i, name) in ["red", "white", "black"].iter().enumerate() {
+            let stitch = Stitch::new('*', Some(Color::from_str(name)?));
+            chart.set_stitch(0u8.into(), (i as u8).into(), stitch)?;
+        }
+
+        assert!(invert_chart(&chart).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_invert_one_color() {
+        let mut chart = chart!("*.")?;
+        chart.set_stitch(
+            0u8.into(),
+            0u8.into(),
+            Stitch::new('*', Some(Color::from_str("red")?)),
+        )?;
+
+        assert!(matches!(invert_chart(&chart), Err(Error::OneColor { .. })));
+    }
+
+    #[throws]
+    #[test]
+    fn test_replace() {
+        let chart = chart!(
+            "*.x",
+            ".*x")?;
+
+        let swapped = replace_symbols(&chart, &[('*', '.'), ('.', '*')])?;
+        assert_eq!(swapped.write_to_string()?, chart_str!(".*x", "*.x"));
+
+        let replaced = replace_symbols(&chart, &[('x', 'o')])?;
+        assert_eq!(replaced.write_to_string()?, chart_str!("*.o", ".*o"));
+    }
+}
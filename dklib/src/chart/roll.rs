@@ -0,0 +1,108 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows};
+use crate::Error;
+use fehler::throws;
+use std::convert::TryFrom;
+
+// Move every stitch `dx` columns to the right and `dy` rows down (negative values move
+// left and up). Stitches that fall off one edge wrap around to the opposite edge.
+#[throws]
+pub fn roll_chart(chart: &Chart, dx: i32, dy: i32) -> Chart {
+    let rows = i64::from(u32::from(chart.rows()));
+    let cols = i64::from(u32::from(chart.cols()));
+
+    let mut rolled = Chart::new(chart.cols(), chart.rows());
+    rolled.inherit_metadata(chart);
+    for row in chart.rows() {
+        for col in chart.cols() {
+            let new_row = (i64::from(u32::from(row)) + i64::from(dy)).rem_euclid(rows);
+            let new_col = (i64::from(u32::from(col)) + i64::from(dx)).rem_euclid(cols);
+            rolled.set_stitch(
+                Rows::try_from(new_row as u32)?,
+                Cols::try_from(new_col as u32)?,
+                chart.stitch(row, col)?.clone(),
+            )?;
+        }
+    }
+
+    rolled
+}
+
+// Move every stitch `dx` columns to the right and `dy` rows down (negative values move
+// left and up). Stitches that fall off the edge are dropped, and the space they leave
+// behind is filled with `background`.
+#[throws]
+pub fn shift_chart(chart: &Chart, dx: i32, dy: i32, background: char) -> Chart {
+    let rows = i64::from(u32::from(chart.rows()));
+    let cols = i64::from(u32::from(chart.cols()));
+
+    let mut shifted = Chart::new(chart.cols(), chart.rows());
+    shifted.inherit_metadata(chart);
+    for row in shifted.rows() {
+        for col in shifted.cols() {
+            let old_row = i64::from(u32::from(row)) - i64::from(dy);
+            let old_col = i64::from(u32::from(col)) - i64::from(dx);
+            let stitch = if (0..rows).contains(&old_row) && (0..cols).contains(&old_col) {
+                chart
+                    .stitch(
+                        Rows::try_from(old_row as u32)?,
+                        Cols::try_from(old_col as u32)?,
+                    )?
+                    .clone()
+            } else {
+                Stitch::new(background, None)
+            };
+            shifted.set_stitch(row, col, stitch)?;
+        }
+    }
+
+    shifted
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_roll() {
+        let chart = chart!(
+            "*...",
+            ".*..",
+            "...."
+        )?;
+
+        let rolled = roll_chart(&chart, 3, 1)?;
+        let rolled_str = chart_str!(
+            "....",
+            "...*",
+            "*..."
+        );
+        assert_eq!(rolled.write_to_string()?, rolled_str);
+
+        let back = roll_chart(&rolled, -3, -1)?;
+        assert_eq!(back.write_to_string()?, chart.write_to_string()?);
+
+        let around = roll_chart(&chart, 8, -6)?;
+        assert_eq!(around.write_to_string()?, chart.write_to_string()?);
+    }
+
+    #[throws]
+    #[test]
+    fn test_shift() {
+        let chart = chart!(
+            "ab",
+            "cd"
+        )?;
+
+        let shifted = shift_chart(&chart, 1, 0, '.')?;
+        assert_eq!(shifted.write_to_string()?, chart_str!(".a", ".c"));
+
+        let shifted = shift_chart(&chart, -1, -1, '.')?;
+        assert_eq!(shifted.write_to_string()?, chart_str!("d.", ".."));
+
+        let gone = shift_chart(&chart, 5, 0, '.')?;
+        assert_eq!(gone.write_to_string()?, chart_str!("..", ".."));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Height, Rows, Width};
+use crate::Error;
+use assure::assure;
+use fehler::throws;
+use std::convert::TryFrom;
+
+// Enlarge `chart` so that each stitch becomes an `h` x `v` block of stitches.
+#[throws]
+pub fn scale_up(chart: &Chart, h: u16, v: u16) -> Chart {
+    assure!(h > 0, Error::ZeroNotLegal { argname: "h" });
+    assure!(v > 0, Error::ZeroNotLegal { argname: "v" });
+
+    let mut scaled = Chart::new(
+        Cols::try_from(u32::from(chart.cols()) * u32::from(h))?,
+        Rows::try_from(u32::from(chart.rows()) * u32::from(v))?,
+    );
+    scaled.inherit_metadata(chart);
+    for row in scaled.rows() {
+        for col in scaled.cols() {
+            scaled.set_stitch(row, col, chart.stitch(row / v, col / h)?.clone())?;
+        }
+    }
+    scaled
+}
+
+// Shrink `chart` by keeping the center stitch of each `h` x `v` block.
+// Any partial blocks at the right and bottom edges are dropped.
+#[throws]
+pub fn scale_down(chart: &Chart, h: u16, v: u16) -> Chart {
+    assure!(h > 0, Error::ZeroNotLegal { argname: "h" });
+    assure!(v > 0, Error::ZeroNotLegal { argname: "v" });
+
+    let mut scaled = Chart::new(chart.cols() / h, chart.rows() / v);
+    scaled.inherit_metadata(chart);
+    for row in scaled.rows() {
+        for col in scaled.cols() {
+            let stitch = chart.stitch(row * v + v / 2, col * h + h / 2)?;
+            scaled.set_stitch(row, col, stitch.clone())?;
+        }
+    }
+    scaled
+}
+
+// Resize `chart` to `width` x `height`.
+//
+// Each new stitch covers a block of stitches in the original chart, and takes the value that
+// appears most often in that block. Ties go to the stitch closest to the top-left of the block.
+#[throws]
+pub fn resize_chart(chart: &Chart, width: Width, height: Height) -> Chart {
+    assure!(
+        width > Width::from(0u8),
+        Error::ZeroNotLegal { argname: "width" }
+    );
+    assure!(
+        height > Height::from(0u8),
+        Error::ZeroNotLegal { argname: "height" }
+    );
+    assure!(
+        chart.rows() > Rows::from(0u8) && chart.cols() > Cols::from(0u8),
+        Error::EmptyChart {
+            msg: "Cannot resize"
+        }
+    );
+
+    let mut resized = Chart::new(width, height);
+    resized.inherit_metadata(chart);
+    for row in resized.rows() {
+        let (top, bottom) = source_span(row.into(), chart.rows().into(), height.into());
+        for col in resized.cols() {
+            let (left, right) = source_span(col.into(), chart.cols().into(), width.into());
+
+            let mut votes: Vec<(&Stitch, u32)> = Vec::new();
+            for src_row in top..bottom {
+                for src_col in left..right {
+                    let stitch =
+                        chart.stitch(Rows::try_from(src_row)?, Cols::try_from(src_col)?)?;
+                    match votes.iter_mut().find(|(s, _)| *s == stitch) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((stitch, 1)),
+                    }
+                }
+            }
+
+            // `max_by_key` prefers the last maximum, so search from the back to favor the first.
+            let winner = votes
+                .iter()
+                .rev()
+                .max_by_key(|(_, count)| *count)
+                .map(|(stitch, _)| (*stitch).clone())
+                .unwrap_or_default();
+            resized.set_stitch(row, col, winner)?;
+        }
+    }
+    resized
+}
+
+// The source indices, [start, end), covered by `index` when resizing `size` to `new_size`.
+// The range always contains at least one index.
+fn source_span(index: u32, size: u32, new_size: u32) -> (u32, u32) {
+    let start = index * size / new_size;
+    let end = (index + 1) * size / new_size;
+    (start, u32::max(end, start + 1))
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_scale_up() {
+        let chart = chart!(
+            "*.",
+            ".*")?;
+
+        let scaled = scale_up(&chart, 2, 3)?;
+        let scaled_str = chart_str!(
+            "**..",
+            "**..",
+            "**..",
+            "..**",
+            "..**",
+            "..**");
+        assert_eq!(scaled.write_to_string()?, scaled_str);
+    }
+
+    #[test]
+    fn test_scale_up_too_big() {
+        let chart = Chart::new(40u8, 1u8);
+        assert!(scale_up(&chart, 2000, 1).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_scale_down() {
+        let chart = chart!(
+            "abcdef",
+            "ghijkl",
+            "mnopqr",
+            "stuvwx")?;
+
+        let scaled = scale_down(&chart, 3, 2)?;
+        assert_eq!(scaled.write_to_string()?, chart_str!("hk", "tw"));
+
+        let round_trip = scale_down(&scale_up(&chart, 2, 2)?, 2, 2)?;
+        assert_eq!(round_trip.write_to_string()?, chart.write_to_string()?);
+
+        assert!(scale_down(&chart, 0, 1).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_resize_majority() {
+        let chart = chart!(
+            "**..",
+            "*...",
+            "..**",
+            ".***")?;
+
+        let resized = resize_chart(&chart, 2u8.into(), 2u8.into())?;
+        assert_eq!(resized.write_to_string()?, chart_str!("*.", ".*"));
+
+        // A 2x2 block with a tie keeps the top-left stitch.
+        let tied = chart!(
+            "*.",
+            ".*")?;
+        let resized = resize_chart(&tied, 1u8.into(), 1u8.into())?;
+        assert_eq!(resized.write_to_string()?, chart_str!("*"));
+    }
+
+    #[throws]
+    #[test]
+    fn test_resize_fractional() {
+        let chart = chart!(
+            "*.*.",
+            "*.*.")?;
+
+        let resized = resize_chart(&chart, 6u8.into(), 1u8.into())?;
+        assert_eq!(resized.write_to_string()?, chart_str!("**.**."));
+    }
+}
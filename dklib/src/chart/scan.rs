@@ -0,0 +1,484 @@
+use crate::Error;
+use crate::{
+    chart::{Chart, ReadingDirection, Stitch},
+    units::{Cols, Height, Rows, Width},
+};
+use css_color_parser::Color;
+use fehler::throws;
+use image::{DynamicImage, Rgb, RgbImage};
+use std::{convert::TryFrom, ops::Range};
+
+/// Controls how `scan_chart_image` reads a picture of a chart.
+#[derive(Clone, Debug)]
+pub struct ScanOptions {
+    /// The symbol for cells with something drawn in them.
+    pub mark: char,
+
+    /// The symbol for cells with nothing drawn in them.
+    pub blank: char,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            mark: '*',
+            blank: '.',
+        }
+    }
+}
+
+// Two colors closer than this in every channel are the same yarn. This absorbs the noise in
+// screenshots and JPEGs.
+const COLOR_TOLERANCE: u8 = 24;
+
+// Read a chart back out of a picture of one, like the PNGs that `the_thing` writes.
+//
+// The cells are found from their boundaries, which are the rows and columns of pixels where the
+// color changes almost all the way across the chart. That covers both grid lines and colored
+// cells that are filled right up to each other. Cells with something drawn in the middle are
+// `mark` stitches, and the rest are `blank`. The symbols themselves aren't recognized, so a '*'
+// dot and any other symbol both read as `mark`.
+//
+// The fill color of each cell becomes the stitch's color, except for white cells, which are
+// uncolored. If there is a column of row numbers on the left of the grid and not on the right,
+// the chart is read from left to right.
+#[throws]
+pub fn scan_chart_image(image: &DynamicImage, options: &ScanOptions) -> Chart {
+    let img = image.to_rgb8();
+    // A grid line changes color on both sides, so it's seen as a pair of boundaries. Symbols
+    // are mostly round or slanted, so their edges are spread out over many rows and columns.
+    let changes = |from: (u32, u32), to: (u32, u32)| {
+        !same_color(
+            *img.get_pixel(from.0, from.1),
+            *img.get_pixel(to.0, to.1),
+            COLOR_TOLERANCE,
+        )
+    };
+    let inside = |x: u32, y: u32| x < img.width() && y < img.height();
+    let starts_row = |x: u32, y: u32| inside(x, y) && y > 0 && changes((x, y - 1), (x, y));
+    let starts_col = |x: u32, y: u32| inside(x, y) && x > 0 && changes((x - 1, y), (x, y));
+
+    let row_counts: Vec<u32> = (0..img.height())
+        .map(|y| (0..img.width()).filter(|&x| starts_row(x, y)).count() as u32)
+        .collect();
+    let col_counts: Vec<u32> = (0..img.width())
+        .map(|x| (0..img.height()).filter(|&y| starts_col(x, y)).count() as u32)
+        .collect();
+    let row_lines = find_lines(&row_counts);
+    let col_lines = find_lines(&col_counts);
+    if row_lines.is_empty() || col_lines.is_empty() {
+        Err(Error::NoChartGrid)?;
+    }
+
+    // Cells are usually about as tall as they are wide. So a chart that is only a row or two
+    // tall can be measured by its columns, and when whole rows (or columns) are the same, the
+    // other way shows that the lines are more than one cell apart.
+    let (row_spacing, col_spacing) = match (line_spacing(&row_lines), line_spacing(&col_lines)) {
+        (Some(rows), Some(cols)) => (finer(rows, cols), finer(cols, rows)),
+        (Some(spacing), None) | (None, Some(spacing)) => (spacing, spacing),
+        (None, None) => Err(Error::NoChartGrid)?,
+    };
+    let mut row_edges = cell_edges(&row_lines, row_spacing);
+    let mut col_edges = cell_edges(&col_lines, col_spacing);
+    extend_edges(&mut row_edges, row_spacing, img.height(), |ys| {
+        crossed_by(&col_lines, ys, starts_col)
+    });
+    extend_edges(&mut col_edges, col_spacing, img.width(), |xs| {
+        crossed_by(&row_lines, xs, |y, x| starts_row(x, y))
+    });
+    if row_edges.len() < 2 || col_edges.len() < 2 {
+        Err(Error::NoChartGrid)?;
+    }
+    let (left, right) = (col_edges[0], col_edges[col_edges.len() - 1]);
+
+    let mut chart = Chart::new(
+        Width::try_from(col_edges.len() - 1)?,
+        Height::try_from(row_edges.len() - 1)?,
+    );
+    let mut palette: Vec<Rgb<u8>> = Vec::new();
+    for (row, ys) in row_edges.windows(2).enumerate() {
+        for (col, xs) in col_edges.windows(2).enumerate() {
+            let (fill, center) = sample_cell(&img, (xs[0], xs[1]), (ys[0], ys[1]));
+            let symbol = if same_color(fill, center, 64) {
+                options.blank
+            } else {
+                options.mark
+            };
+            let color = if is_white(fill) {
+                None
+            } else {
+                Some(palette_color(&mut palette, fill))
+            };
+            chart.set_stitch(
+                Rows::try_from(row)?,
+                Cols::try_from(col)?,
+                Stitch::new(symbol, color),
+            )?;
+        }
+    }
+
+    // Row numbers are drawn inside each row, so there is something in every row and nothing on
+    // the lines between them. A border or anything else beside the grid runs across those lines.
+    let has_numbers = |xs: Range<u32>| {
+        let marked = |y: u32| xs.clone().any(|x| !is_white(*img.get_pixel(x, y)));
+        !xs.is_empty()
+            && row_edges
+                .windows(2)
+                .all(|ys| (ys[0] + 1..ys[1]).any(marked))
+            && !row_edges[1..row_edges.len() - 1].iter().any(|&y| marked(y))
+    };
+    if has_numbers(0..left) && !has_numbers(right + 1..img.width()) {
+        chart.set_reading_direction(ReadingDirection::LeftToRight);
+    }
+
+    chart
+}
+
+fn is_white(pixel: Rgb<u8>) -> bool {
+    pixel
+        .0
+        .iter()
+        .all(|&channel| channel >= 255 - COLOR_TOLERANCE)
+}
+
+fn same_color(a: Rgb<u8>, b: Rgb<u8>, tolerance: u8) -> bool {
+    a.0.iter()
+        .zip(&b.0)
+        .all(|(x, y)| (i16::from(*x) - i16::from(*y)).abs() <= i16::from(tolerance))
+}
+
+// The color in `palette` that `color` matches, adding it if it matches none of them.
+fn palette_color(palette: &mut Vec<Rgb<u8>>, color: Rgb<u8>) -> Color {
+    let found = match palette
+        .iter()
+        .find(|known| same_color(**known, color, COLOR_TOLERANCE))
+    {
+        Some(known) => *known,
+        None => {
+            palette.push(color);
+            color
+        }
+    };
+    Color {
+        r: found.0[0],
+        g: found.0[1],
+        b: found.0[2],
+        a: 1.0,
+    }
+}
+
+// The positions of the lines in a row or column of pixel counts, as the middle of each run of
+// counts that are nearly as high as the highest one.
+//
+// Runs that are much wider than the narrowest run are shading or noise rather than lines, and
+// are skipped.
+fn find_lines(counts: &[u32]) -> Vec<u32> {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return Vec::new();
+    }
+
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for (index, count) in counts.iter().enumerate() {
+        if *count < max * 4 / 5 {
+            continue;
+        }
+        let index = index as u32;
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => runs.push((index, index)),
+        }
+    }
+
+    let narrowest = runs.iter().map(|(start, end)| end - start + 1).min();
+    runs.iter()
+        .filter(|(start, end)| Some(end - start + 1) <= narrowest.map(|width| width * 3))
+        .map(|(start, end)| (start + end) / 2)
+        .collect()
+}
+
+// The size of the cells between the lines, or None if there aren't enough lines to tell.
+//
+// Neighboring cells of the same color have no boundary between them, so this is the smallest
+// gap between lines that all of the other gaps are close to a multiple of.
+fn line_spacing(lines: &[u32]) -> Option<u32> {
+    // Anything smaller is too small to have a color and a symbol in it.
+    let mut gaps: Vec<u32> = lines
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|gap| *gap >= 4)
+        .collect();
+    gaps.sort_unstable();
+    gaps.iter()
+        .copied()
+        .find(|&spacing| gaps.iter().all(|&gap| is_multiple(gap, spacing)))
+}
+
+// `other` if `spacing` is several of them, or else `spacing`.
+fn finer(spacing: u32, other: u32) -> u32 {
+    if spacing >= 2 * other && is_multiple(spacing, other) {
+        other
+    } else {
+        spacing
+    }
+}
+
+fn is_multiple(gap: u32, spacing: u32) -> bool {
+    let off = gap % spacing;
+    off.min(spacing - off) <= spacing / 4
+}
+
+// The boundaries of the cells between the first and last of the grid lines that were found.
+//
+// A line that is missing (e.g. between cells of the same color) still gets a boundary. Boundaries are moved
+// onto nearby lines, since images that have been scaled don't have cells that are all the same
+// size.
+fn cell_edges(lines: &[u32], spacing: u32) -> Vec<u32> {
+    let (first, last) = (lines[0], lines[lines.len() - 1]);
+    let cells = ((last - first) as f32 / spacing as f32).round() as u32;
+    if cells == 0 {
+        return vec![first];
+    }
+    (0..=cells)
+        .map(|cell| {
+            let edge = first + (last - first) * cell / cells;
+            lines
+                .iter()
+                .copied()
+                .find(|line| (*line as i64 - edge as i64).abs() <= i64::from(spacing / 4))
+                .unwrap_or(edge)
+        })
+        .collect()
+}
+
+// Add cells past the outermost edges for as long as `is_cell` says that the band of pixels
+// there is part of the grid. The border of the grid is often cropped off, or is missing
+// entirely, as in the images from `the_thing`, so room for just one more cell before the edge of
+// the image is always a cell.
+fn extend_edges(
+    edges: &mut Vec<u32>,
+    spacing: u32,
+    limit: u32,
+    is_cell: impl Fn(Range<u32>) -> bool,
+) {
+    let slack = spacing / 4;
+    let fits = |room: u32| room + slack >= spacing;
+    let cut_off = |room: u32| fits(room) && room <= spacing + slack;
+
+    loop {
+        let first = edges[0];
+        let start = first.saturating_sub(spacing);
+        if !fits(first) || !(cut_off(first) || is_cell(start + 1..first)) {
+            break;
+        }
+        edges.insert(0, start);
+    }
+    loop {
+        let last = edges[edges.len() - 1];
+        let end = (last + spacing).min(limit - 1);
+        let room = limit - 1 - last;
+        if !fits(room) || !(cut_off(room) || is_cell(last + 1..end + 1)) {
+            break;
+        }
+        edges.push(end);
+    }
+}
+
+// Whether the `lines` running across the band carry on more than halfway through it.
+// Lines that aren't perfectly straight may wander a pixel to either side.
+fn crossed_by(lines: &[u32], band: Range<u32>, on_line: impl Fn(u32, u32) -> bool) -> bool {
+    let total = lines.len() * band.len();
+    let dark = lines
+        .iter()
+        .map(|&line| {
+            band.clone()
+                .filter(|&pos| (line.saturating_sub(1)..=line + 1).any(|near| on_line(near, pos)))
+                .count()
+        })
+        .sum::<usize>();
+    total > 0 && dark * 2 > total
+}
+
+// Returns the (fill, center) colors of the cell between the lines at `xs` and `ys`.
+//
+// The fill comes from the corners of the cell, which are clear of the symbol, and the center
+// is the average of the few pixels in the middle.
+fn sample_cell(img: &RgbImage, xs: (u32, u32), ys: (u32, u32)) -> (Rgb<u8>, Rgb<u8>) {
+    let inset_x = ((xs.1 - xs.0) / 7).max(2);
+    let inset_y = ((ys.1 - ys.0) / 7).max(2);
+    let corners = [
+        img.get_pixel(xs.0 + inset_x, ys.0 + inset_y),
+        img.get_pixel(xs.1 - inset_x, ys.0 + inset_y),
+        img.get_pixel(xs.0 + inset_x, ys.1 - inset_y),
+        img.get_pixel(xs.1 - inset_x, ys.1 - inset_y),
+    ];
+    // A symbol that reaches into a corner only spoils one channel's median in one corner.
+    let mut fill = [0; 3];
+    for (channel, value) in fill.iter_mut().enumerate() {
+        let mut values: Vec<u8> = corners.iter().map(|pixel| pixel.0[channel]).collect();
+        values.sort_unstable();
+        *value = values[2];
+    }
+
+    let (center_x, center_y) = ((xs.0 + xs.1) / 2, (ys.0 + ys.1) / 2);
+    let mut sums = [0_u32; 3];
+    let mut samples = 0;
+    for x in center_x - 1..=center_x + 1 {
+        for y in center_y - 1..=center_y + 1 {
+            for (sum, value) in sums.iter_mut().zip(&img.get_pixel(x, y).0) {
+                *sum += u32::from(*value);
+            }
+            samples += 1;
+        }
+    }
+    let center = [sums[0] / samples, sums[1] / samples, sums[2] / samples];
+
+    (
+        Rgb(fill),
+        Rgb([center[0] as u8, center[1] as u8, center[2] as u8]),
+    )
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::thing::render_chart;
+    use image::imageops::FilterType;
+    use image::GenericImageView;
+
+    #[throws]
+    fn scan(chart: &Chart, options: &ScanOptions) -> Chart {
+        let image = DynamicImage::ImageRgb8(render_chart(chart, &[])?);
+        scan_chart_image(&image, options)?
+    }
+
+    #[throws]
+    #[test]
+    fn test_scan_rendered_chart() {
+        let chart = chart!(
+            "*..*",
+            ".**.",
+            "*..*")?;
+        assert_eq!(
+            scan(&chart, &ScanOptions::default())?.write_to_string()?,
+            chart.write_to_string()?
+        );
+
+        // Like a screenshot, which is rarely the size the chart was drawn at.
+        let image = render_chart(&chart, &[])?;
+        for (width, height) in &[(180, 90), (150, 75), (120, 60)] {
+            let scaled = image::imageops::resize(&image, *width, *height, FilterType::Triangle);
+            let scanned =
+                scan_chart_image(&DynamicImage::ImageRgb8(scaled), &ScanOptions::default())?;
+            assert_eq!(
+                scanned.write_to_string()?,
+                chart.write_to_string()?,
+                "{}x{}",
+                width,
+                height
+            );
+        }
+    }
+
+    // The row numbers are what show the reading direction, and they need the font.
+    #[cfg(feature = "embedded-font")]
+    #[throws]
+    #[test]
+    fn test_scan_colors() {
+        let chart = chart!(
+            "* *",
+            " * ")?;
+        let mut merged = chart.merge_with(&chart)?;
+        merged.set_reading_direction(ReadingDirection::LeftToRight);
+        let options = ScanOptions {
+            blank: ' ',
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            scan(&merged, &options)?.write_to_string()?,
+            merged.write_to_string()?
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_scan_left_border() {
+        let chart = chart!(
+            "*..*",
+            ".**.",
+            "*..*")?;
+        // Cut off the row numbers on the right, which are two cells wide, but not the last grid
+        // line. Then move the grid over to make room for a bar on the left.
+        let rendered = render_chart(&chart, &[])?;
+        let grid_width = rendered.width() - 29;
+        let mut image = RgbImage::from_pixel(grid_width + 30, rendered.height(), Rgb([255; 3]));
+        image::imageops::replace(
+            &mut image,
+            &rendered.view(0, 0, grid_width, rendered.height()),
+            30,
+            0,
+        );
+        for x in 4..8 {
+            for y in 5..25 {
+                image.put_pixel(x, y, Rgb([0; 3]));
+            }
+        }
+
+        let scanned = scan_chart_image(&DynamicImage::ImageRgb8(image), &ScanOptions::default())?;
+        assert_eq!(scanned.write_to_string()?, chart.write_to_string()?);
+    }
+
+    #[test]
+    fn test_scan_no_grid() {
+        let blank = DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 40, Rgb([255, 255, 255])));
+        assert!(matches!(
+            scan_chart_image(&blank, &ScanOptions::default()),
+            Err(Error::NoChartGrid)
+        ));
+    }
+
+    #[test]
+    fn test_scan_one_row_of_lines() {
+        // The ticks make a single row line, so there are no rows of cells between lines.
+        let mut img = RgbImage::from_pixel(100, 100, Rgb([255, 255, 255]));
+        for x in (10..100).step_by(20) {
+            for y in 50..52 {
+                img.put_pixel(x, y, Rgb([0, 0, 0]));
+            }
+        }
+        assert!(matches!(
+            scan_chart_image(&DynamicImage::ImageRgb8(img), &ScanOptions::default()),
+            Err(Error::NoChartGrid)
+        ));
+    }
+
+    #[test]
+    fn test_cell_edges() {
+        assert_eq!(line_spacing(&[0, 14, 29, 44]), Some(14));
+        assert_eq!(cell_edges(&[0, 14, 29, 44], 14), vec![0, 14, 29, 44]);
+        // The line at 29 is missing, and the scaled cells aren't all the same size.
+        assert_eq!(line_spacing(&[0, 15, 44, 60]), Some(15));
+        assert_eq!(cell_edges(&[0, 15, 44, 60], 15), vec![0, 15, 30, 44, 60]);
+        // Cells of the same color next to each other.
+        assert_eq!(line_spacing(&[15, 45, 75, 90]), Some(15));
+        assert_eq!(line_spacing(&[10]), None);
+        assert_eq!(line_spacing(&[10, 12, 14]), None);
+        assert_eq!(line_spacing(&[0, 10, 25]), None);
+        assert_eq!(finer(30, 15), 15);
+        assert_eq!(finer(15, 30), 15);
+        assert_eq!(finer(20, 15), 20);
+    }
+
+    #[test]
+    fn test_extend_edges() {
+        // Only the inner lines of a 4 cell grid are there.
+        let mut edges = vec![15, 30, 45];
+        extend_edges(&mut edges, 15, 60, |_| false);
+        assert_eq!(edges, vec![0, 15, 30, 45, 59]);
+
+        let mut edges = vec![40, 55];
+        extend_edges(&mut edges, 15, 100, |band| band.start > 40);
+        assert_eq!(edges, vec![40, 55, 70, 85, 99]);
+    }
+}
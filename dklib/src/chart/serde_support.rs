@@ -0,0 +1,157 @@
+// Serialization for the chart types that can't just derive it.
+//
+// Colors, stitch kinds and reading directions are written the same way as in a chart file,
+// e.g. "#daa520", "cable left 4" and "left-to-right", so that the two formats are easy to
+// translate between.
+
+use crate::chart::{Chart, LegendEntry, ReadingDirection, Stitch, StitchKind};
+use crate::units::{Cols, Rows};
+use crate::Error;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub(crate) mod optional_color {
+    use crate::chart::hex_color;
+    use css_color_parser::Color;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => serializer.serialize_some(&hex_color(*color)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(value) => Color::from_str(&value)
+                .map(Some)
+                .map_err(|_| de::Error::custom(format!("'{}' is not a valid color", value))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn serialize_display<S: Serializer>(
+    value: &impl Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = Error>,
+{
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value).map_err(de::Error::custom)
+}
+
+impl Serialize for StitchKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StitchKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+impl Serialize for ReadingDirection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ReadingDirection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+// A chart as it is deserialized, before checking that the size matches the stitches.
+#[derive(Deserialize)]
+pub(crate) struct ChartData {
+    stitches: Vec<Vec<Stitch>>,
+    #[serde(default)]
+    legend: Vec<LegendEntry>,
+    #[serde(default)]
+    reading_direction: Option<ReadingDirection>,
+    rows: Rows,
+    cols: Cols,
+}
+
+impl TryFrom<ChartData> for Chart {
+    type Error = Error;
+
+    fn try_from(data: ChartData) -> Result<Self, Self::Error> {
+        let fits = data.stitches.len() == usize::from(data.rows)
+            && data
+                .stitches
+                .iter()
+                .all(|row| row.len() == usize::from(data.cols));
+        if !fits {
+            return Err(Error::SizeMismatch {
+                rows: data.rows.into(),
+                cols: data.cols.into(),
+            });
+        }
+        Ok(Chart {
+            stitches: data.stitches,
+            legend: data.legend,
+            reading_direction: data.reading_direction,
+            rows: data.rows,
+            cols: data.cols,
+        })
+    }
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fehler::throws;
+
+    #[throws]
+    #[test]
+    fn test_json_round_trip() {
+        let chart = chart!(
+            "*.",
+            ".*")?;
+        let mut merged = chart.merge_with(&chart)?;
+        merged.set_legend(vec![
+            LegendEntry::new('*', "front"),
+            LegendEntry::for_kind('╱', StitchKind::CableLeft { width: 4 }),
+        ]);
+        merged.set_reading_direction(ReadingDirection::LeftToRight);
+
+        let json = serde_json::to_string(&merged).unwrap();
+        assert!(json.contains(r##""color":"#daa520""##));
+        assert!(json.contains(r#""kind":"cable left 4""#));
+        assert!(json.contains(r#""reading_direction":"left-to-right""#));
+
+        let read: Chart = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.write_to_string()?, merged.write_to_string()?);
+    }
+
+    #[test]
+    fn test_json_size_mismatch() {
+        let json = r#"{"stitches": [[{"symbol": "*", "color": null}]], "rows": 1, "cols": 2}"#;
+        assert!(serde_json::from_str::<Chart>(json).is_err());
+
+        let json = r#"{"stitches": [[{"symbol": "*"}]], "rows": 1, "cols": 1}"#;
+        let chart: Chart = serde_json::from_str(json).unwrap();
+        assert_eq!(chart.reading_direction(), ReadingDirection::RightToLeft);
+    }
+}
@@ -1,28 +1,194 @@
+use crate::chart::crop::crop_chart;
 use crate::chart::Chart;
+use crate::units::{Cols, Height, Rows, Width};
 use crate::Error;
+use assure::assure;
 use fehler::throws;
+use std::{convert::TryFrom, str::FromStr};
+
+/// What to do when a chart doesn't divide evenly into the requested number of panels.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OddPolicy {
+    /// The leftmost (or topmost) panels get the extra stitches.
+    Left,
+    /// The rightmost (or bottommost) panels get the extra stitches.
+    Right,
+    /// Refuse to split the chart.
+    Error,
+    /// All panels are the same size, and the stitches at the boundaries are
+    /// duplicated to make up the difference.
+    Duplicate,
+}
+
+impl FromStr for OddPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(OddPolicy::Left),
+            "right" => Ok(OddPolicy::Right),
+            "error" => Ok(OddPolicy::Error),
+            "duplicate" => Ok(OddPolicy::Duplicate),
+            _ => Err(Error::UnknownOption {
+                name: "odd width policy",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
 
 #[throws]
 pub fn split_chart(chart: &Chart) -> (Chart, Chart) {
-    // TODO: deal with odd widths.
-    let split_point = chart.cols() / 2;
+    // Odd widths put the extra column on the right.
+    split_at(chart, chart.cols() / 2)?
+}
+
+// Split `chart` into two charts, with `col` being the first column of the right chart.
+#[throws]
+pub fn split_at(chart: &Chart, col: Cols) -> (Chart, Chart) {
+    assure!(
+        col <= chart.cols(),
+        Error::RangeCheck {
+            name: "Col",
+            value: col.into(),
+            max: u32::from(chart.cols()) + 1
+        }
+    );
+
+    (
+        extract(chart, 0, 0, chart.rows().into(), col.into())?,
+        extract(
+            chart,
+            0,
+            col.into(),
+            chart.rows().into(),
+            (chart.cols() - col).into(),
+        )?,
+    )
+}
+
+// Split `chart` into two charts, with `row` being the first row of the bottom chart.
+#[throws]
+pub fn split_at_row(chart: &Chart, row: Rows) -> (Chart, Chart) {
+    assure!(
+        row <= chart.rows(),
+        Error::RangeCheck {
+            name: "Row",
+            value: row.into(),
+            max: u32::from(chart.rows()) + 1
+        }
+    );
 
-    // TODO: deal with charts with variable number of columns per row.
-    //       perhaps pad the rows after reading in the file?
-    let mut left_chart = Chart::new(split_point, chart.rows());
-    let mut right_chart = Chart::new(chart.cols() - split_point, chart.rows());
+    (
+        extract(chart, 0, 0, row.into(), chart.cols().into())?,
+        extract(
+            chart,
+            row.into(),
+            0,
+            (chart.rows() - row).into(),
+            chart.cols().into(),
+        )?,
+    )
+}
+
+// Split `chart` into `n` panels from left to right.
+//
+// Each panel except the last also includes the first `overlap` columns of the panel to its right.
+#[throws]
+pub fn split_into(chart: &Chart, n: u16, policy: OddPolicy, overlap: u16) -> Vec<Chart> {
+    let spans = panel_spans("cols", chart.cols().into(), n, policy, overlap)?;
+    spans
+        .into_iter()
+        .map(|(start, len)| extract(chart, 0, start, chart.rows().into(), len))
+        .collect::<crate::Result<Vec<_>>>()?
+}
+
+// Split `chart` into `n` panels from top to bottom.
+//
+// Each panel except the last also includes the first `overlap` rows of the panel below it.
+#[throws]
+pub fn split_rows_into(chart: &Chart, n: u16, policy: OddPolicy, overlap: u16) -> Vec<Chart> {
+    let spans = panel_spans("rows", chart.rows().into(), n, policy, overlap)?;
+    spans
+        .into_iter()
+        .map(|(start, len)| extract(chart, start, 0, len, chart.cols().into()))
+        .collect::<crate::Result<Vec<_>>>()?
+}
+
+// Computes the (start, length) of each of `n` panels dividing `size` stitches.
+#[throws]
+fn panel_spans(
+    dimen: &'static str,
+    size: u32,
+    n: u16,
+    policy: OddPolicy,
+    overlap: u16,
+) -> Vec<(u32, u32)> {
+    let n = u32::from(n);
+    assure!(n > 0, Error::ZeroNotLegal { argname: "n" });
+    assure!(
+        n <= size,
+        Error::RangeCheck {
+            name: "Panel count",
+            value: n,
+            max: size + 1
+        }
+    );
 
-    for row in chart.rows() {
-        for col in split_point {
-            left_chart.set_stitch(row, col, chart.stitch(row, col)?.clone())?;
+    let base = size / n;
+    let extra = size % n;
+    assure!(
+        extra == 0 || policy != OddPolicy::Error,
+        Error::UnevenSplit {
+            dimen,
+            size,
+            panels: n
         }
+    );
 
-        for col in chart.cols() - split_point {
-            right_chart.set_stitch(row, col, chart.stitch(row, col + split_point)?.clone())?;
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for i in 0..n {
+        let len = match policy {
+            OddPolicy::Left => base + u32::from(i < extra),
+            OddPolicy::Right => base + u32::from(i >= n - extra),
+            OddPolicy::Error => base,
+            OddPolicy::Duplicate => base + u32::from(extra > 0),
+        };
+        spans.push((start, len));
+
+        // With duplication, the first (n - extra) boundaries share a column.
+        start += len;
+        if policy == OddPolicy::Duplicate && extra > 0 && i < n - extra {
+            start -= 1;
         }
     }
 
-    (left_chart, right_chart)
+    // Extend every panel but the last into its neighbor.
+    let last = spans.len() - 1;
+    for (start, len) in &mut spans[..last] {
+        *len = u32::min(*len + u32::from(overlap), size - *start);
+    }
+
+    spans
+}
+
+// Copy a rectangle out of `chart`. The rectangle must fit inside `chart`, but unlike
+// `crop_chart` it may be empty, as one side of a split at the edge of the chart is.
+#[throws]
+fn extract(chart: &Chart, top: u32, left: u32, height: u32, width: u32) -> Chart {
+    if height == 0 || width == 0 {
+        let mut empty = Chart::new(Width::try_from(width)?, Height::try_from(height)?);
+        empty.inherit_metadata(chart);
+        return empty;
+    }
+    crop_chart(
+        chart,
+        Rows::try_from(top)?,
+        Cols::try_from(left)?,
+        Height::try_from(height)?,
+        Width::try_from(width)?,
+    )?
 }
 
 #[rustfmt::skip::macros(chart, chart_str)]
@@ -61,5 +227,88 @@ mod test {
         assert_eq!(right.write_to_string()?, chart_right_str);
     }
 
-    // TODO: test_split_odd (and left and right)
+    #[throws]
+    #[test]
+    fn test_split_odd() {
+        let chart = chart!(
+            "abcde",
+            "fghij")?;
+
+        let (left, right) = split_chart(&chart)?;
+        assert_eq!(left.write_to_string()?, chart_str!("ab", "fg"));
+        assert_eq!(right.write_to_string()?, chart_str!("cde", "hij"));
+
+        let panels = split_into(&chart, 2, OddPolicy::Left, 0)?;
+        assert_eq!(panels[0].write_to_string()?, chart_str!("abc", "fgh"));
+        assert_eq!(panels[1].write_to_string()?, chart_str!("de", "ij"));
+
+        let panels = split_into(&chart, 2, OddPolicy::Duplicate, 0)?;
+        assert_eq!(panels[0].write_to_string()?, chart_str!("abc", "fgh"));
+        assert_eq!(panels[1].write_to_string()?, chart_str!("cde", "hij"));
+
+        assert!(split_into(&chart, 2, OddPolicy::Error, 0).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_split_into() {
+        let chart = chart!(
+            "abcdefghij")?;
+
+        let panels = split_into(&chart, 3, OddPolicy::Right, 0)?;
+        let strs = panels
+            .iter()
+            .map(|panel| panel.write_to_string())
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            strs,
+            vec![chart_str!("abc"), chart_str!("def"), chart_str!("ghij")]
+        );
+
+        let panels = split_into(&chart, 3, OddPolicy::Duplicate, 0)?;
+        let strs = panels
+            .iter()
+            .map(|panel| panel.write_to_string())
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            strs,
+            vec![chart_str!("abcd"), chart_str!("defg"), chart_str!("ghij")]
+        );
+
+        let panels = split_into(&chart, 3, OddPolicy::Left, 2)?;
+        let strs = panels
+            .iter()
+            .map(|panel| panel.write_to_string())
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(
+            strs,
+            vec![chart_str!("abcdef"), chart_str!("efghi"), chart_str!("hij")]
+        );
+
+        assert!(split_into(&chart, 0, OddPolicy::Left, 0).is_err());
+        assert!(split_into(&chart, 11, OddPolicy::Left, 0).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_split_rows() {
+        let chart = chart!(
+            "a",
+            "b",
+            "c")?;
+
+        let (top, bottom) = split_at_row(&chart, 1u8.into())?;
+        assert_eq!(top.write_to_string()?, chart_str!("a"));
+        assert_eq!(bottom.write_to_string()?, chart_str!("b", "c"));
+
+        let (top, bottom) = split_at_row(&chart, 3u8.into())?;
+        assert_eq!(top.write_to_string()?, chart.write_to_string()?);
+        assert_eq!(bottom.rows(), Rows::from(0u8));
+
+        let panels = split_rows_into(&chart, 3, OddPolicy::Error, 0)?;
+        assert_eq!(panels.len(), 3);
+        assert_eq!(panels[2].write_to_string()?, chart_str!("c"));
+
+        assert!(split_at_row(&chart, 4u8.into()).is_err());
+    }
 }
@@ -0,0 +1,253 @@
+use crate::chart::reader::check_row_length;
+use crate::chart::{hex_color, Chart, ReadOptions, ReadWarning, Stitch};
+use crate::{Error, Location, ParseError};
+use css_color_parser::Color;
+use fehler::throws;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+// Read a chart from CSV, with one cell per stitch, like a chart sketched in a spreadsheet.
+//
+// The first character of a cell is the stitch's symbol. Anything after it is the stitch's color,
+// e.g. "*#daa520" or "* goldenrod". Empty cells are '.' stitches, the same as the padding for
+// short rows. Rows of different lengths are handled the same as in a chart file.
+#[throws]
+pub fn read_csv(
+    rdr: impl Read,
+    file: Option<&Path>,
+    options: &ReadOptions,
+) -> (Chart, Vec<ReadWarning>) {
+    let mut warnings = Vec::new();
+    let mut stitches: Vec<Vec<Stitch>> = Vec::new();
+    for record in csv_reader(rdr).records() {
+        let record = record?;
+        let line = CsvLine::new(&record, file);
+        if let Some(first_row) = stitches.first() {
+            check_row_length(
+                options,
+                first_row.len(),
+                record.len(),
+                |column| line.location(column),
+                &mut warnings,
+            )?;
+        }
+
+        let mut current_row = Vec::new();
+        for (index, cell) in record.iter().enumerate() {
+            let mut chars = cell.chars();
+            let stitch = match chars.next() {
+                Some(symbol) => Stitch::new(symbol, parse_color(chars.as_str(), &line, index)?),
+                None => Stitch::new('.', None),
+            };
+            current_row.push(stitch);
+        }
+        stitches.push(current_row);
+    }
+
+    (Chart::from_rows(stitches)?, warnings)
+}
+
+// Color the stitches in `chart` from a CSV grid of colors that lines up with the chart.
+// Empty cells leave the stitch alone, and cells outside of the chart are ignored.
+#[throws]
+pub fn read_csv_colors(chart: &mut Chart, rdr: impl Read, file: Option<&Path>) {
+    for (row, record) in chart.stitches.iter_mut().zip(csv_reader(rdr).records()) {
+        let record = record?;
+        let line = CsvLine::new(&record, file);
+        for (index, (stitch, cell)) in row.iter_mut().zip(record.iter()).enumerate() {
+            if let Some(color) = parse_color(cell, &line, index)? {
+                stitch.color = Some(color);
+            }
+        }
+    }
+}
+
+// Write `chart` as CSV with one cell per stitch, in the form that `read_csv` reads.
+// If `inline_colors` is set, each stitch's color follows its symbol in the cell.
+#[throws]
+pub fn write_csv(chart: &Chart, w: &mut dyn Write, inline_colors: bool) {
+    let mut writer = csv::Writer::from_writer(w);
+    for row in &chart.stitches {
+        writer.write_record(row.iter().map(|stitch| match stitch.color() {
+            Some(color) if inline_colors => format!("{}{}", stitch.symbol(), hex_color(color)),
+            _ => stitch.symbol().to_string(),
+        }))?;
+    }
+    writer.flush()?;
+}
+
+// Write the colors of `chart` as a CSV grid, in the form that `read_csv_colors` reads.
+#[throws]
+pub fn write_csv_colors(chart: &Chart, w: &mut dyn Write) {
+    let mut writer = csv::Writer::from_writer(w);
+    for row in &chart.stitches {
+        writer.write_record(row.iter().map(|stitch| match stitch.color() {
+            Some(color) => hex_color(color),
+            None => String::new(),
+        }))?;
+    }
+    writer.flush()?;
+}
+
+fn csv_reader(rdr: impl Read) -> csv::Reader<impl Read> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        // Ragged rows are checked by `read_csv` instead.
+        .flexible(true)
+        .from_reader(rdr)
+}
+
+#[throws]
+fn parse_color(cell: &str, line: &CsvLine, index: usize) -> Option<Color> {
+    let value = cell.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match Color::from_str(value) {
+        Ok(color) => Some(color),
+        Err(_) => Err(Error::Parse {
+            location: line.location(index + 1),
+            kind: ParseError::BadColor {
+                value: value.to_string(),
+            },
+        })?,
+    }
+}
+
+// A CSV record rebuilt as a line of text, so that errors can point at one of its cells.
+struct CsvLine<'a> {
+    file: Option<&'a Path>,
+    line: usize,
+    text: String,
+    // The character column where each cell starts, starting at 1.
+    columns: Vec<usize>,
+}
+
+impl<'a> CsvLine<'a> {
+    fn new(record: &csv::StringRecord, file: Option<&'a Path>) -> CsvLine<'a> {
+        let mut columns = Vec::new();
+        let mut column = 1;
+        for cell in record.iter() {
+            columns.push(column);
+            column += cell.chars().count() + 1;
+        }
+        CsvLine {
+            file,
+            line: record.position().map_or(0, |pos| pos.line() as usize),
+            text: record.iter().collect::<Vec<_>>().join(","),
+            columns,
+        }
+    }
+
+    // The location of cell number `cell`, starting at 1. Cells past the end of the record
+    // point just past the end of the line.
+    fn location(&self, cell: usize) -> Location {
+        Location {
+            file: self.file.map(Path::to_path_buf),
+            line: self.line,
+            column: self
+                .columns
+                .get(cell - 1)
+                .copied()
+                .unwrap_or_else(|| self.text.chars().count() + 1),
+            text: self.text.clone(),
+        }
+    }
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chart::ReadMode;
+
+    #[throws]
+    #[test]
+    fn test_read_csv() {
+        let csv = "*,.,*\n*,,.#ff0000\n○goldenrod, ,.\n";
+        let (chart, warnings) = read_csv(csv.as_bytes(), None, &ReadOptions::default())?;
+        assert!(warnings.is_empty());
+        assert_eq!(
+            chart.write_to_string()?,
+            chart_str!(
+                "*.*",
+                "*..",
+                "○ .",
+                "",
+                "COLORS",
+                "a #ff0000",
+                "b #daa520",
+                "",
+                "...",
+                "..a",
+                "b.."
+            )
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_csv_ragged() {
+        let csv = "*,*,*\n*\n";
+        let (chart, _) = read_csv(csv.as_bytes(), None, &ReadOptions::default())?;
+        assert_eq!(chart.write_to_string()?, chart_str!("***", "*.."));
+
+        let warn = ReadOptions {
+            mode: ReadMode::Warn,
+        };
+        let (_, warnings) = read_csv(csv.as_bytes(), None, &warn)?;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            (warnings[0].location.line, warnings[0].location.column),
+            (2, 2)
+        );
+
+        let strict = ReadOptions {
+            mode: ReadMode::Strict,
+        };
+        assert!(read_csv(csv.as_bytes(), None, &strict).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_read_csv_bad_color() {
+        match read_csv("*,*\n*,*nope\n".as_bytes(), None, &ReadOptions::default()) {
+            Err(Error::Parse {
+                location,
+                kind: ParseError::BadColor { value },
+            }) => {
+                assert_eq!(value, "nope");
+                assert_eq!(location.snippet(), "2 | *,*nope\n  |   ^");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[throws]
+    #[test]
+    fn test_write_csv() {
+        let chart = chart!(
+            "*.",
+            ".*")?;
+        let merged = chart.merge_with(&chart)?;
+
+        let mut symbols = Vec::new();
+        write_csv(&merged, &mut symbols, false)?;
+        assert_eq!(
+            String::from_utf8(symbols.clone()).unwrap(),
+            "*, ,*, \n*, ,*, \n"
+        );
+
+        let mut colors = Vec::new();
+        write_csv_colors(&merged, &mut colors)?;
+        let (mut read, _) = read_csv(symbols.as_slice(), None, &ReadOptions::default())?;
+        read_csv_colors(&mut read, colors.as_slice(), None)?;
+        assert_eq!(read.write_to_string()?, merged.write_to_string()?);
+
+        let mut inline = Vec::new();
+        write_csv(&merged, &mut inline, true)?;
+        let (read, _) = read_csv(inline.as_slice(), None, &ReadOptions::default())?;
+        assert_eq!(read.write_to_string()?, merged.write_to_string()?);
+    }
+}
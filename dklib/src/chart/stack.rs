@@ -0,0 +1,143 @@
+use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows};
+use crate::Error;
+use assure::assure;
+use fehler::throws;
+use std::{convert::TryFrom, str::FromStr};
+
+/// Horizontal placement of a chart that is narrower than the charts around it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl HAlign {
+    // The number of columns to skip before placing something `width` wide into `total`.
+    pub(crate) fn offset(self, width: Cols, total: Cols) -> Cols {
+        match self {
+            HAlign::Left => Cols::from(0u8),
+            HAlign::Center => (total - width) / 2,
+            HAlign::Right => total - width,
+        }
+    }
+}
+
+impl FromStr for HAlign {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(HAlign::Left),
+            "center" => Ok(HAlign::Center),
+            "right" => Ok(HAlign::Right),
+            _ => Err(Error::UnknownOption {
+                name: "horizontal alignment",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+// Stack `charts` top to bottom, in order.
+//
+// Charts narrower than the widest chart are placed according to `align`, and the
+// rest of their rows are filled with `background`.
+#[throws]
+pub fn stack_charts(charts: &[&Chart], align: HAlign, background: char) -> Chart {
+    assure!(
+        !charts.is_empty(),
+        Error::ZeroNotLegal {
+            argname: "number of charts"
+        }
+    );
+
+    let width = charts
+        .iter()
+        .map(|chart| chart.cols())
+        .max()
+        .unwrap_or_else(|| Cols::from(0u8));
+    let height = Rows::try_from(
+        charts
+            .iter()
+            .map(|chart| u32::from(chart.rows()))
+            .sum::<u32>(),
+    )?;
+
+    let mut stacked = Chart::new(width, height);
+    for chart in charts {
+        stacked.inherit_metadata(chart);
+    }
+    let mut top = Rows::from(0u8);
+    for chart in charts {
+        let left = align.offset(chart.cols(), width);
+        for row in chart.rows() {
+            for col in width {
+                let stitch = if col >= left && col - left < chart.cols() {
+                    chart.stitch(row, col - left)?.clone()
+                } else {
+                    Stitch::new(background, None)
+                };
+                stacked.set_stitch(top + row, col, stitch)?;
+            }
+        }
+        top = top + chart.rows();
+    }
+
+    stacked
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_stack() {
+        let top = chart!(
+            "*..*",
+            ".**.")?;
+        let bottom = chart!(
+            "****")?;
+
+        let stacked = stack_charts(&[&top, &bottom, &top], HAlign::Left, '.')?;
+        let stacked_str = chart_str!(
+            "*..*",
+            ".**.",
+            "****",
+            "*..*",
+            ".**.");
+        assert_eq!(stacked.write_to_string()?, stacked_str);
+    }
+
+    #[throws]
+    #[test]
+    fn test_stack_align() {
+        let wide = chart!(
+            "*****")?;
+        let narrow = chart!(
+            "**")?;
+
+        let left = stack_charts(&[&wide, &narrow], HAlign::Left, '.')?;
+        assert_eq!(left.write_to_string()?, chart_str!("*****", "**..."));
+
+        let center = stack_charts(&[&wide, &narrow], HAlign::Center, '.')?;
+        assert_eq!(center.write_to_string()?, chart_str!("*****", ".**.."));
+
+        let right = stack_charts(&[&wide, &narrow], HAlign::Right, '.')?;
+        assert_eq!(right.write_to_string()?, chart_str!("*****", "...**"));
+    }
+
+    #[test]
+    fn test_stack_nothing() {
+        assert!(stack_charts(&[], HAlign::Left, '.').is_err());
+    }
+
+    #[test]
+    fn test_stack_too_tall() {
+        let chart = Chart::new(1u8, u16::MAX);
+        assert!(stack_charts(&[&chart, &chart], HAlign::Left, '.').is_err());
+    }
+}
@@ -5,17 +5,30 @@ use fehler::throws;
 
 #[throws]
 pub fn stamp_chart(chart: &Chart, stamp: &Chart, h_offset: Cols, v_offset: Rows) -> Chart {
+    stamp_chart_with(chart, stamp, h_offset, v_offset, None)?
+}
+
+// Like `stamp_chart`, but stitches in `stamp` with the `transparent` symbol are skipped,
+// leaving the stitches of `chart` underneath them alone.
+#[throws]
+pub fn stamp_chart_with(
+    chart: &Chart,
+    stamp: &Chart,
+    h_offset: Cols,
+    v_offset: Rows,
+    transparent: Option<char>,
+) -> Chart {
     let mut stamped = chart.clone();
     for stamp_row in stamp.rows {
         for stamp_col in stamp.cols {
             let chart_row = v_offset + stamp_row;
             let chart_col = h_offset + stamp_col;
+            let stitch = stamp.stitch(stamp_row, stamp_col)?;
+            if Some(stitch.symbol()) == transparent {
+                continue;
+            }
             if chart_row < stamped.rows && chart_col < stamped.cols {
-                stamped.set_stitch(
-                    v_offset + stamp_row,
-                    h_offset + stamp_col,
-                    stamp.stitch(stamp_row, stamp_col)?.clone(),
-                )?;
+                stamped.set_stitch(v_offset + stamp_row, h_offset + stamp_col, stitch.clone())?;
             }
         }
     }
@@ -90,4 +103,35 @@ mod test {
         );
         assert_eq!(stamped.write_to_string()?, stamped_str);
     }
+
+    #[throws]
+    #[test]
+    fn test_stamp_transparent() {
+        let big_chart = chart!(
+            "*****",
+            "*****",
+            "*****"
+        )?;
+
+        let small_chart = chart!(
+            "~.~",
+            ".x.",
+            "~.~"
+        )?;
+
+        let stamped = stamp_chart_with(
+            &big_chart,
+            &small_chart,
+            1_u8.into(),
+            0_u8.into(),
+            Some('~'),
+        )?;
+
+        let stamped_str = chart_str!(
+            "**.**",
+            "*.x.*",
+            "**.**"
+        );
+        assert_eq!(stamped.write_to_string()?, stamped_str);
+    }
 }
@@ -0,0 +1,175 @@
+use crate::chart::region::{label_regions, Connectivity};
+use crate::chart::Chart;
+use crate::units::{Cols, Height, Rows, Width};
+use crate::Error;
+use css_color_parser::Color;
+use fehler::throws;
+use std::convert::TryFrom;
+
+/// Summary numbers about a chart, from `Chart::stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartStats {
+    pub rows: Rows,
+    pub cols: Cols,
+
+    /// The number of stitches with each symbol, in the order the symbols first appear.
+    pub symbol_counts: Vec<(char, u32)>,
+    /// The number of stitches with each color, in the order the colors first appear.
+    /// Stitches without a color are not counted.
+    pub color_counts: Vec<(Color, u32)>,
+    /// The longest horizontal run of each symbol, in the same order as `symbol_counts`.
+    pub longest_runs: Vec<(char, u32)>,
+
+    /// The number of connected regions of identical stitches.
+    pub region_count: u32,
+    /// The top, left, height, and width of the smallest rectangle holding all of the
+    /// non-empty stitches, or `None` if the chart is entirely empty.
+    pub bounds: Option<(Rows, Cols, Height, Width)>,
+
+    /// True if the chart is the same when flipped left-to-right.
+    pub left_right_symmetric: bool,
+    /// True if the chart is the same when flipped top-to-bottom.
+    pub top_bottom_symmetric: bool,
+}
+
+#[throws]
+pub fn chart_stats(chart: &Chart, connectivity: Connectivity) -> ChartStats {
+    let mut symbol_counts: Vec<(char, u32)> = Vec::new();
+    let mut color_counts: Vec<(Color, u32)> = Vec::new();
+    let mut longest_runs: Vec<(char, u32)> = Vec::new();
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+    for (r, row) in chart.stitches.iter().enumerate() {
+        let mut run = 0;
+        for (c, stitch) in row.iter().enumerate() {
+            let symbol = stitch.symbol();
+            match symbol_counts.iter_mut().find(|(s, _)| *s == symbol) {
+                Some((_, count)) => *count += 1,
+                None => {
+                    symbol_counts.push((symbol, 1));
+                    longest_runs.push((symbol, 0));
+                }
+            }
+
+            if let Some(color) = stitch.color() {
+                match color_counts.iter_mut().find(|(c, _)| *c == color) {
+                    Some((_, count)) => *count += 1,
+                    None => color_counts.push((color, 1)),
+                }
+            }
+
+            run = if c > 0 && row[c - 1].symbol() == symbol {
+                run + 1
+            } else {
+                1
+            };
+            if let Some((_, longest)) = longest_runs.iter_mut().find(|(s, _)| *s == symbol) {
+                *longest = u32::max(*longest, run);
+            }
+
+            if !stitch.is_empty() {
+                bounds = Some(match bounds {
+                    None => (r, c, r, c),
+                    Some((top, left, bottom, right)) => (
+                        usize::min(top, r),
+                        usize::min(left, c),
+                        usize::max(bottom, r),
+                        usize::max(right, c),
+                    ),
+                });
+            }
+        }
+    }
+
+    let bounds = match bounds {
+        None => None,
+        Some((top, left, bottom, right)) => Some((
+            Rows::try_from(top)?,
+            Cols::try_from(left)?,
+            Height::try_from(bottom - top + 1)?,
+            Width::try_from(right - left + 1)?,
+        )),
+    };
+
+    let left_right_symmetric = chart
+        .stitches
+        .iter()
+        .all(|row| row.iter().eq(row.iter().rev()));
+    let top_bottom_symmetric = chart.stitches.iter().eq(chart.stitches.iter().rev());
+
+    ChartStats {
+        rows: chart.rows(),
+        cols: chart.cols(),
+        symbol_counts,
+        color_counts,
+        longest_runs,
+        region_count: u32::try_from(label_regions(chart, connectivity)?.len())?,
+        bounds,
+        left_right_symmetric,
+        top_bottom_symmetric,
+    }
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_stats() {
+        let chart = chart!(
+            "......",
+            ".***..",
+            ".*.**.",
+            "......"
+        )?;
+
+        let stats = chart_stats(&chart, Connectivity::Four)?;
+        assert_eq!((stats.rows, stats.cols), (4u8.into(), 6u8.into()));
+        assert_eq!(stats.symbol_counts, vec![('.', 18), ('*', 6)]);
+        assert!(stats.color_counts.is_empty());
+        assert_eq!(stats.longest_runs, vec![('.', 6), ('*', 3)]);
+        assert_eq!(stats.region_count, 2);
+        assert_eq!(
+            stats.bounds,
+            Some((1u8.into(), 1u8.into(), 2u8.into(), 4u8.into()))
+        );
+        assert!(!stats.left_right_symmetric);
+        assert!(!stats.top_bottom_symmetric);
+    }
+
+    #[throws]
+    #[test]
+    fn test_stats_symmetry() {
+        let chart = chart!(
+            "*..*",
+            ".**.",
+            "*..*"
+        )?;
+
+        let stats = chart_stats(&chart, Connectivity::Four)?;
+        assert!(stats.left_right_symmetric);
+        assert!(stats.top_bottom_symmetric);
+
+        let empty = chart!(
+            "..",
+            ".."
+        )?;
+        assert_eq!(chart_stats(&empty, Connectivity::Four)?.bounds, None);
+    }
+
+    #[throws]
+    #[test]
+    fn test_stats_colors() {
+        let chart = chart!(
+            "*..",
+            ".*."
+        )?;
+        let merged = chart.merge_with(&chart)?;
+
+        let stats = chart_stats(&merged, Connectivity::Four)?;
+        let counts: Vec<u32> = stats.color_counts.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, vec![6, 6]);
+    }
+}
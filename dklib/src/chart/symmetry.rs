@@ -0,0 +1,277 @@
+use crate::chart::Chart;
+use crate::units::{Cols, Rows};
+use crate::Error;
+use assure::assure;
+use fehler::throws;
+use std::{convert::TryFrom, str::FromStr};
+
+/// The kinds of symmetry that `symmetrize_chart` can build from a single piece of a motif.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymmetryMode {
+    /// The chart is the left half. Mirror it left to right.
+    Mirror,
+    /// The chart is the top-left quadrant. Mirror it across both axes.
+    Quad,
+    /// The chart is the left half. Rotate it 180° to make the right half.
+    Rotate2,
+    /// The chart is the (square) top-left quadrant. Rotate it 90° for each of the others.
+    Rotate4,
+}
+
+impl FromStr for SymmetryMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mirror" => Ok(SymmetryMode::Mirror),
+            "quad" => Ok(SymmetryMode::Quad),
+            "rotate2" => Ok(SymmetryMode::Rotate2),
+            "rotate4" => Ok(SymmetryMode::Rotate4),
+            _ => Err(Error::UnknownOption {
+                name: "symmetry mode",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+// Build a symmetric chart from `chart`, which is one half, quadrant, or wedge of the result.
+//
+// If `shared_center` is set, the center row and/or column of the result is the last
+// row/column of `chart` and appears only once. Otherwise, it is duplicated.
+// (For `Rotate4`, a shared center means that the bottom row of `chart` is where the rotated
+// right column goes, so the two have to match.)
+#[throws]
+pub fn symmetrize_chart(chart: &Chart, mode: SymmetryMode, shared_center: bool) -> Chart {
+    assure!(
+        chart.rows() > Rows::from(0u8) && chart.cols() > Cols::from(0u8),
+        Error::EmptyChart {
+            msg: "Cannot symmetrize"
+        }
+    );
+
+    let overlap = u32::from(shared_center);
+    let width = u32::from(chart.cols());
+    let height = u32::from(chart.rows());
+
+    let (out_width, out_height) = match mode {
+        SymmetryMode::Mirror | SymmetryMode::Rotate2 => (2 * width - overlap, height),
+        SymmetryMode::Quad => (2 * width - overlap, 2 * height - overlap),
+        SymmetryMode::Rotate4 => {
+            assure!(
+                width == height,
+                Error::NotSquare {
+                    msg: "Cannot rotate",
+                    rows: height,
+                    cols: width
+                }
+            );
+            let out_size = 2 * width - overlap;
+            if shared_center {
+                for col in 0..width - 1 {
+                    let (src_row, src_col) = unrotate(height - 1, col, width, out_size, true);
+                    let bottom = Rows::try_from(height - 1)?;
+                    assure!(
+                        chart.stitch(bottom, Cols::try_from(col)?)?
+                            == chart.stitch(Rows::try_from(src_row)?, Cols::try_from(src_col)?)?,
+                        Error::MismatchedCenter { col }
+                    );
+                }
+            }
+            (out_size, out_size)
+        }
+    };
+
+    let mut symmetric = Chart::new(Cols::try_from(out_width)?, Rows::try_from(out_height)?);
+    symmetric.inherit_metadata(chart);
+    for row in symmetric.rows() {
+        for col in symmetric.cols() {
+            let (src_row, src_col) = match mode {
+                SymmetryMode::Mirror => (row.into(), mirror(col.into(), width, out_width)),
+                SymmetryMode::Quad => (
+                    mirror(row.into(), height, out_height),
+                    mirror(col.into(), width, out_width),
+                ),
+                SymmetryMode::Rotate2 => {
+                    if u32::from(col) < width {
+                        (row.into(), col.into())
+                    } else {
+                        (height - 1 - u32::from(row), out_width - 1 - u32::from(col))
+                    }
+                }
+                SymmetryMode::Rotate4 => {
+                    unrotate(row.into(), col.into(), width, out_width, shared_center)
+                }
+            };
+            let stitch = chart.stitch(Rows::try_from(src_row)?, Cols::try_from(src_col)?)?;
+            symmetric.set_stitch(row, col, stitch.clone())?;
+        }
+    }
+
+    symmetric
+}
+
+// Maps an index in the output back to the index in the source that it reflects.
+fn mirror(index: u32, size: u32, out_size: u32) -> u32 {
+    if index < size {
+        index
+    } else {
+        out_size - 1 - index
+    }
+}
+
+// Rotate (row, col) counter-clockwise about the center of the output square
+// until it lands in the part of the output that is copied straight from the source.
+//
+// With a shared center, the copied part is the source minus its bottom row (plus the center
+// stitch), since the bottom row is where the rotated right column ends up.
+fn unrotate(row: u32, col: u32, size: u32, out_size: u32, shared_center: bool) -> (u32, u32) {
+    let in_source = |row: u32, col: u32| {
+        if shared_center {
+            (row + 1 < size && col < size) || (row + 1 == size && col + 1 == size)
+        } else {
+            row < size && col < size
+        }
+    };
+
+    let (mut row, mut col) = (row, col);
+    while !in_source(row, col) {
+        let rotated = (out_size - 1 - col, row);
+        row = rotated.0;
+        col = rotated.1;
+    }
+    (row, col)
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[throws]
+    #[test]
+    fn test_mirror() {
+        let chart = chart!(
+            "*..",
+            ".*.",
+            "..*")?;
+
+        let doubled = symmetrize_chart(&chart, SymmetryMode::Mirror, false)?;
+        assert_eq!(
+            doubled.write_to_string()?,
+            chart_str!(
+                "*....*",
+                ".*..*.",
+                "..**..")
+        );
+
+        let shared = symmetrize_chart(&chart, SymmetryMode::Mirror, true)?;
+        assert_eq!(
+            shared.write_to_string()?,
+            chart_str!(
+                "*...*",
+                ".*.*.",
+                "..*..")
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_quad() {
+        let chart = chart!(
+            "*.",
+            ".*")?;
+
+        let shared = symmetrize_chart(&chart, SymmetryMode::Quad, true)?;
+        assert_eq!(
+            shared.write_to_string()?,
+            chart_str!(
+                "*.*",
+                ".*.",
+                "*.*")
+        );
+
+        let doubled = symmetrize_chart(&chart, SymmetryMode::Quad, false)?;
+        assert_eq!(
+            doubled.write_to_string()?,
+            chart_str!(
+                "*..*",
+                ".**.",
+                ".**.",
+                "*..*")
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_rotate2() {
+        let chart = chart!(
+            "ab",
+            "cd")?;
+
+        let rotated = symmetrize_chart(&chart, SymmetryMode::Rotate2, false)?;
+        assert_eq!(
+            rotated.write_to_string()?,
+            chart_str!(
+                "abdc",
+                "cdba")
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_rotate4() {
+        let chart = chart!(
+            "ab",
+            "cd")?;
+
+        let rotated = symmetrize_chart(&chart, SymmetryMode::Rotate4, false)?;
+        assert_eq!(
+            rotated.write_to_string()?,
+            chart_str!(
+                "abca",
+                "cddb",
+                "bddc",
+                "acba")
+        );
+
+        // The bottom row has to be the right column, rotated.
+        assert!(symmetrize_chart(&chart, SymmetryMode::Rotate4, true).is_err());
+        let chart = chart!(
+            "ab",
+            "bd")?;
+        let shared = symmetrize_chart(&chart, SymmetryMode::Rotate4, true)?;
+        assert_eq!(
+            shared.write_to_string()?,
+            chart_str!(
+                "aba",
+                "bdb",
+                "aba")
+        );
+
+        let wedge = chart!(
+            "abc",
+            "def",
+            "cfi")?;
+        let pinwheel = symmetrize_chart(&wedge, SymmetryMode::Rotate4, true)?;
+        assert_eq!(
+            pinwheel.write_to_string()?,
+            chart_str!(
+                "abcda",
+                "defeb",
+                "cfifc",
+                "befed",
+                "adcba")
+        );
+
+        let not_square = chart!("abc")?;
+        assert!(symmetrize_chart(&not_square, SymmetryMode::Rotate4, false).is_err());
+    }
+
+    #[test]
+    fn test_mode_from_str() {
+        assert_eq!(SymmetryMode::Quad, "quad".parse().unwrap());
+        assert_eq!(SymmetryMode::Rotate4, "rotate4".parse().unwrap());
+        assert!("sideways".parse::<SymmetryMode>().is_err());
+    }
+}
@@ -3,17 +3,116 @@ use crate::{
     units::{Cols, Rows},
 };
 use crate::{Error, Result};
+use assure::assure;
 use fehler::throws;
-use std::iter::Iterator;
+use std::{cmp::min, convert::TryFrom, iter::Iterator};
+
+/// Controls which sides `trim_chart_with` trims, and how much it leaves behind.
+#[derive(Clone, Debug)]
+pub struct TrimOptions {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+
+    /// The number of empty stitches to leave on each trimmed side (if there are that many).
+    pub margin: u16,
+
+    /// If set, trimming an empty chart is not an error. The result has no rows if the top or
+    /// bottom is trimmed, and no columns if the left or right is.
+    pub allow_empty: bool,
+}
+
+impl TrimOptions {
+    /// Trim only the top and bottom.
+    pub fn rows_only() -> TrimOptions {
+        TrimOptions {
+            left: false,
+            right: false,
+            ..TrimOptions::default()
+        }
+    }
+
+    /// Trim only the left and right sides.
+    pub fn cols_only() -> TrimOptions {
+        TrimOptions {
+            top: false,
+            bottom: false,
+            ..TrimOptions::default()
+        }
+    }
+}
+
+impl Default for TrimOptions {
+    fn default() -> Self {
+        TrimOptions {
+            top: true,
+            bottom: true,
+            left: true,
+            right: true,
+            margin: 0,
+            allow_empty: false,
+        }
+    }
+}
 
 #[throws]
 pub fn trim_chart(chart: &Chart) -> Chart {
-    let top = find_top(&chart)?;
-    let bottom = find_bottom(&chart)?;
-    let left = find_left(&chart)?;
-    let right = find_right(&chart)?;
+    trim_chart_with(chart, &TrimOptions::default())?
+}
+
+#[throws]
+pub fn trim_chart_with(chart: &Chart, options: &TrimOptions) -> Chart {
+    if is_empty(chart)? {
+        assure!(
+            options.allow_empty,
+            Error::EmptyChart { msg: "Cannot trim" }
+        );
+        // Only the trimmed sides disappear, so trimming just the rows keeps the width.
+        let width = if options.left || options.right {
+            Cols::from(0u8)
+        } else {
+            chart.cols()
+        };
+        let height = if options.top || options.bottom {
+            Rows::from(0u8)
+        } else {
+            chart.rows()
+        };
+        let mut empty = Chart::new(width, height);
+        empty.inherit_metadata(chart);
+        return empty;
+    }
+
+    let row_margin = Rows::from(options.margin);
+    let col_margin = Cols::from(options.margin);
+    let top = if options.top {
+        let found = find_top(chart)?;
+        found - min(found, row_margin)
+    } else {
+        Rows::from(0u8)
+    };
+    let bottom = if options.bottom {
+        let found = u32::from(find_bottom(chart)?) + u32::from(options.margin);
+        Rows::try_from(min(found, u32::from(chart.rows()) - 1))?
+    } else {
+        chart.rows() - 1
+    };
+    let left = if options.left {
+        let found = find_left(chart)?;
+        found - min(found, col_margin)
+    } else {
+        Cols::from(0u8)
+    };
+    let right = if options.right {
+        let found = u32::from(find_right(chart)?) + u32::from(options.margin);
+        Cols::try_from(min(found, u32::from(chart.cols()) - 1))?
+    } else {
+        chart.cols() - 1
+    };
 
     let mut trimmed = Chart::new(right - left + 1, bottom - top + 1);
+    trimmed.inherit_metadata(chart);
     for row in bottom - top + 1 {
         for col in right - left + 1 {
             let stitch = chart.stitch(row + top, col + left)?.clone();
@@ -24,6 +123,18 @@ pub fn trim_chart(chart: &Chart) -> Chart {
     trimmed
 }
 
+#[throws]
+fn is_empty(chart: &Chart) -> bool {
+    for row in chart.rows() {
+        for col in chart.cols() {
+            if !chart.stitch(row, col)?.is_empty() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
 fn find_top(chart: &Chart) -> Result<Rows> {
     for row in chart.rows() {
         for col in chart.cols() {
@@ -95,4 +206,109 @@ mod test {
 
         assert_eq!(trimmed.write_to_string()?, trimmed_str);
     }
+
+    #[throws]
+    #[test]
+    fn test_trim_sides() {
+        let chart = chart!(
+            ".........",
+            "...**....",
+            "...*.....",
+            "........."
+        )?;
+
+        let cols_only = trim_chart_with(&chart, &TrimOptions::cols_only())?;
+        assert_eq!(
+            cols_only.write_to_string()?,
+            chart_str!("..", "**", "*.", "..")
+        );
+
+        let rows_only = trim_chart_with(&chart, &TrimOptions::rows_only())?;
+        assert_eq!(
+            rows_only.write_to_string()?,
+            chart_str!("...**....", "...*.....")
+        );
+
+        let left_only = TrimOptions {
+            top: false,
+            bottom: false,
+            right: false,
+            ..TrimOptions::default()
+        };
+        let trimmed = trim_chart_with(&chart, &left_only)?;
+        assert_eq!(
+            trimmed.write_to_string()?,
+            chart_str!("......", "**....", "*.....", "......")
+        );
+    }
+
+    #[throws]
+    #[test]
+    fn test_trim_margin() {
+        let chart = chart!(
+            ".........",
+            "...**....",
+            ".........",
+            "........."
+        )?;
+
+        let options = TrimOptions {
+            margin: 2,
+            ..TrimOptions::default()
+        };
+        let trimmed = trim_chart_with(&chart, &options)?;
+        let trimmed_str = chart_str!(
+            "......",
+            "..**..",
+            "......",
+            "......"
+        );
+        assert_eq!(trimmed.write_to_string()?, trimmed_str);
+
+        // A margin bigger than the chart keeps all of it.
+        let options = TrimOptions {
+            margin: u16::MAX,
+            ..TrimOptions::default()
+        };
+        let trimmed = trim_chart_with(&chart, &options)?;
+        assert_eq!(trimmed.write_to_string()?, chart.write_to_string()?);
+    }
+
+    #[throws]
+    #[test]
+    fn test_trim_empty() {
+        let chart = chart!(
+            "....",
+            "...."
+        )?;
+
+        assert!(trim_chart(&chart).is_err());
+
+        let options = TrimOptions {
+            allow_empty: true,
+            ..TrimOptions::default()
+        };
+        let trimmed = trim_chart_with(&chart, &options)?;
+        assert_eq!(trimmed.write_to_string()?, chart_str!());
+
+        let rows_only = TrimOptions {
+            allow_empty: true,
+            ..TrimOptions::rows_only()
+        };
+        let trimmed = trim_chart_with(&chart, &rows_only)?;
+        assert_eq!(
+            (trimmed.rows(), trimmed.cols()),
+            (Rows::from(0u8), Cols::from(4u8))
+        );
+
+        let cols_only = TrimOptions {
+            allow_empty: true,
+            ..TrimOptions::cols_only()
+        };
+        let trimmed = trim_chart_with(&chart, &cols_only)?;
+        assert_eq!(
+            (trimmed.rows(), trimmed.cols()),
+            (Rows::from(2u8), Cols::from(0u8))
+        );
+    }
 }
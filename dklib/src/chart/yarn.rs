@@ -0,0 +1,103 @@
+use crate::chart::merge::merge_charts;
+use crate::chart::region::Connectivity;
+use crate::chart::stats::chart_stats;
+use crate::chart::Chart;
+use crate::Error;
+use assure::assure;
+use css_color_parser::Color;
+use fehler::throws;
+
+/// How much of one color of yarn a chart needs, from `Chart::estimate_yarn`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct YarnUsage {
+    pub color: Color,
+    pub stitches: u32,
+    pub yards: f64,
+}
+
+impl YarnUsage {
+    pub fn meters(&self) -> f64 {
+        self.yards * 0.9144
+    }
+}
+
+// Estimate the yarn needed for each color in `chart`, given the yarn used by a single stitch.
+//
+// Colored charts, like those made by `merge_charts`, are counted as they are: every column is
+// one stitch of a pair, knit with a single strand of the stitch's color. A chart without colors
+// is treated as one face of a double-knitting design, so it is merged with itself first, and
+// each of its stitches becomes a pair that uses one stitch of each color.
+#[throws]
+pub fn estimate_yarn(chart: &Chart, yards_per_stitch: f64) -> Vec<YarnUsage> {
+    assure!(
+        yards_per_stitch > 0.0,
+        Error::ZeroNotLegal {
+            argname: "yards per stitch"
+        }
+    );
+
+    let mut stats = chart_stats(chart, Connectivity::Four)?;
+    if stats.color_counts.is_empty() {
+        stats = chart_stats(&merge_charts(chart, chart)?, Connectivity::Four)?;
+    }
+
+    stats
+        .color_counts
+        .into_iter()
+        .map(|(color, stitches)| YarnUsage {
+            color,
+            stitches,
+            yards: f64::from(stitches) * yards_per_stitch,
+        })
+        .collect()
+}
+
+#[rustfmt::skip::macros(chart, chart_str)]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[throws]
+    #[test]
+    fn test_estimate_merged() {
+        let front = chart!(
+            "**.",
+            "..."
+        )?;
+        let back = chart!(
+            "***",
+            "***"
+        )?;
+        let merged = front.merge_with(&back)?;
+
+        let usage = estimate_yarn(&merged, 0.5)?;
+        let blue = Color::from_str("lightblue")?;
+        let gold = Color::from_str("goldenrod")?;
+
+        // Front: 4 knits in blue, 2 purls in gold. Back: 6 purls in blue.
+        assert_eq!(usage.len(), 2);
+        let blue_usage = usage.iter().find(|u| u.color == blue).unwrap();
+        let gold_usage = usage.iter().find(|u| u.color == gold).unwrap();
+        assert_eq!(blue_usage.stitches, 10);
+        assert_eq!(gold_usage.stitches, 2);
+        assert!((blue_usage.yards - 5.0).abs() < 1e-9);
+        assert!((gold_usage.meters() - 0.9144).abs() < 1e-9);
+    }
+
+    #[throws]
+    #[test]
+    fn test_estimate_uncolored() {
+        let chart = chart!(
+            "**.",
+            "..."
+        )?;
+
+        // Every stitch pair uses one stitch of each color.
+        let usage = estimate_yarn(&chart, 1.0)?;
+        let stitches: Vec<u32> = usage.iter().map(|u| u.stitches).collect();
+        assert_eq!(stitches, vec![6, 6]);
+
+        assert!(estimate_yarn(&chart, 0.0).is_err());
+    }
+}
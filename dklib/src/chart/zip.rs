@@ -1,34 +1,119 @@
 use crate::chart::{Chart, Stitch};
+use crate::units::{Cols, Rows};
 use crate::Error;
+use assure::assure;
 use fehler::throws;
-use std::cmp::max;
+use std::{convert::TryFrom, str::FromStr};
 
+/// Vertical placement of a chart that is shorter than the charts beside it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl VAlign {
+    // The number of rows to skip before placing something `height` tall into `total`.
+    pub(crate) fn offset(self, height: Rows, total: Rows) -> Rows {
+        match self {
+            VAlign::Top => Rows::from(0u8),
+            VAlign::Center => (total - height) / 2,
+            VAlign::Bottom => total - height,
+        }
+    }
+}
+
+impl FromStr for VAlign {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(VAlign::Top),
+            "center" => Ok(VAlign::Center),
+            "bottom" => Ok(VAlign::Bottom),
+            _ => Err(Error::UnknownOption {
+                name: "vertical alignment",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Controls the spacing and alignment of charts zipped together by `zip_charts`.
+#[derive(Clone, Debug)]
+pub struct ZipOptions {
+    /// The number of columns to put between each pair of charts.
+    pub gutter: u16,
+    /// The stitch to fill the gutter with.
+    pub gutter_stitch: char,
+    /// Where to put charts that are shorter than the tallest chart.
+    pub align: VAlign,
+    /// The stitch to fill the space above or below the shorter charts with.
+    pub background: char,
+}
+
+impl Default for ZipOptions {
+    fn default() -> Self {
+        ZipOptions {
+            gutter: 0,
+            gutter_stitch: '.',
+            align: VAlign::Top,
+            background: '.',
+        }
+    }
+}
+
+// Zip `charts` together side-by-side, from left to right.
 #[throws]
-pub fn zip_charts(left_chart: &Chart, right_chart: &Chart) -> Chart {
-    let mut zipped = Chart::new(
-        left_chart.cols() + right_chart.cols(),
-        max(left_chart.rows(), right_chart.rows()),
+pub fn zip_charts(charts: &[&Chart], options: &ZipOptions) -> Chart {
+    assure!(
+        !charts.is_empty(),
+        Error::ZeroNotLegal {
+            argname: "number of charts"
+        }
     );
 
-    for row in zipped.rows() {
-        for col in zipped.cols() {
-            let stitch = if col < left_chart.cols() {
-                // We're in the left chart.
-                if row >= left_chart.rows() {
-                    // TODO: arbitrary stitches
-                    Stitch::new('.', None)
-                } else {
-                    left_chart.stitch(row, col)?.clone()
+    let gutter = Cols::from(options.gutter);
+    let height = charts
+        .iter()
+        .map(|chart| chart.rows())
+        .max()
+        .unwrap_or_else(|| Rows::from(0u8));
+    let width = charts
+        .iter()
+        .map(|chart| u32::from(chart.cols()))
+        .sum::<u32>()
+        + u32::from(options.gutter) * (charts.len() as u32 - 1);
+    let width = Cols::try_from(width)?;
+
+    let mut zipped = Chart::new(width, height);
+    for chart in charts {
+        zipped.inherit_metadata(chart);
+    }
+    let mut left = Cols::from(0u8);
+    for (i, chart) in charts.iter().enumerate() {
+        if i > 0 {
+            for col in gutter {
+                for row in height {
+                    zipped.set_stitch(row, left + col, Stitch::new(options.gutter_stitch, None))?;
                 }
-            } else if row >= right_chart.rows() {
-                // TODO: arbitrary stitches
-                Stitch::new('.', None)
-            } else {
-                right_chart.stitch(row, col - left_chart.cols())?.clone()
-            };
-
-            zipped.set_stitch(row, col, stitch.clone())?;
+            }
+            left = left + gutter;
         }
+
+        let top = options.align.offset(chart.rows(), height);
+        for row in height {
+            for col in chart.cols() {
+                let stitch = if row >= top && row - top < chart.rows() {
+                    chart.stitch(row - top, col)?.clone()
+                } else {
+                    Stitch::new(options.background, None)
+                };
+                zipped.set_stitch(row, left + col, stitch)?;
+            }
+        }
+        left = left + chart.cols();
     }
 
     zipped
@@ -58,7 +143,7 @@ mod test {
             "..**.."
         )?;
 
-        let zipped = zip_charts(&left, &right)?;
+        let zipped = zip_charts(&[&left, &right], &ZipOptions::default())?;
 
         let zipped_str = chart_str!(
             "*...***..**",
@@ -71,4 +156,58 @@ mod test {
 
         assert_eq!(zipped.write_to_string()?, zipped_str);
     }
+
+    #[throws]
+    #[test]
+    fn test_zip_many_with_gutter() {
+        let a = chart!(
+            "**",
+            "**")?;
+        let b = chart!(
+            "*")?;
+
+        let options = ZipOptions {
+            gutter: 1,
+            gutter_stitch: '|',
+            align: VAlign::Bottom,
+            background: ' ',
+        };
+        let zipped = zip_charts(&[&a, &b, &a], &options)?;
+        let zipped_str = chart_str!(
+            "**| |**",
+            "**|*|**");
+        assert_eq!(zipped.write_to_string()?, zipped_str);
+    }
+
+    #[test]
+    fn test_zip_too_wide() {
+        let chart = Chart::new(2u8, 1u8);
+        let options = ZipOptions {
+            gutter: u16::MAX,
+            ..ZipOptions::default()
+        };
+        assert!(zip_charts(&[&chart, &chart], &options).is_err());
+    }
+
+    #[throws]
+    #[test]
+    fn test_zip_center() {
+        let tall = chart!(
+            "*",
+            "*",
+            "*")?;
+        let short = chart!(
+            "x")?;
+
+        let options = ZipOptions {
+            align: VAlign::Center,
+            ..ZipOptions::default()
+        };
+        let zipped = zip_charts(&[&tall, &short], &options)?;
+        let zipped_str = chart_str!(
+            "*.",
+            "*x",
+            "*.");
+        assert_eq!(zipped.write_to_string()?, zipped_str);
+    }
 }
@@ -6,10 +6,18 @@ pub mod chart;
 mod thing;
 mod units;
 
-pub use thing::the_thing;
+pub use thing::{the_thing, the_thing_highlighted};
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[error("The embedded font could not be read")]
+    BadFont,
+
     #[error("Computed {dimen}, {value}, exceeds max width of {max}")]
     ChartTooBig {
         dimen: &'static str,
@@ -27,8 +35,30 @@ pub enum Error {
         rows2: u32,
     },
 
-    #[error("Header terminated too early. Did you forget 'CHART'?")]
-    IncompleteHeader,
+    #[error("Cannot rotate with a shared center unless the bottom row matches the rotated right column. (Column {col} doesn't.)")]
+    MismatchedCenter { col: u32 },
+
+    #[error("Could not find the grid of a chart in the image")]
+    NoChartGrid,
+
+    #[error("{msg} a chart that is not square. ({rows} rows != {cols} cols)")]
+    NotSquare {
+        msg: &'static str,
+        rows: u32,
+        cols: u32,
+    },
+
+    #[error("{msg} a chart with an odd number of columns ({cols})")]
+    OddWidth { msg: &'static str, cols: u32 },
+
+    #[error("{msg} a chart with only one color. (Expected none or 2.)")]
+    OneColor { msg: &'static str },
+
+    #[error("{location}: {kind}")]
+    Parse {
+        location: Location,
+        kind: ParseError,
+    },
 
     #[error("{name} {value} should be less than {max}")]
     RangeCheck {
@@ -37,6 +67,22 @@ pub enum Error {
         max: u32,
     },
 
+    #[error("Chart should have {rows} rows of {cols} stitches, but its stitches don't match")]
+    SizeMismatch { rows: u32, cols: u32 },
+
+    #[error("{msg} a chart with {count} colors. (Expected at most 2.)")]
+    TooManyColors { msg: &'static str, count: usize },
+
+    #[error("Cannot split {size} {dimen} evenly into {panels} panels")]
+    UnevenSplit {
+        dimen: &'static str,
+        size: u32,
+        panels: u32,
+    },
+
+    #[error("'{value}' is not a valid {name}")]
+    UnknownOption { name: &'static str, value: String },
+
     #[error("'0' is not a legal value for {argname}")]
     ZeroNotLegal { argname: &'static str },
 
@@ -47,6 +93,12 @@ pub enum Error {
         source: css_color_parser::ColorParseError,
     },
 
+    #[error("A CSV error occurred: {source}")]
+    Csv {
+        #[from]
+        source: csv::Error,
+    },
+
     #[error("An Image error occurred: {source}")]
     Image {
         #[from]
@@ -65,4 +117,82 @@ pub enum Error {
         source: std::io::Error,
     },
 }
+
+/// The specific problems that can occur while reading a chart file.
+/// These are always wrapped in an `Error::Parse`, which says where the problem is.
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error("'{value}' is not a valid color")]
+    BadColor { value: String },
+
+    #[error(
+        "'{value}' is not a reading direction. (Expected 'right-to-left' or 'left-to-right'.)"
+    )]
+    BadDirection { value: String },
+
+    #[error("legend entries should look like 'SYMBOL DESCRIPTION'")]
+    BadLegendEntry,
+
+    #[error("palette entries should look like 'KEY COLOR'")]
+    BadPaletteEntry,
+
+    #[error("header terminated too early. Did you forget 'CHART'?")]
+    IncompleteHeader,
+
+    #[error("the file is not valid UTF-8")]
+    InvalidUtf8,
+
+    #[error("row has {found} stitches, but the first row has {expected}")]
+    RaggedRow { expected: usize, found: usize },
+
+    #[error("'{key}' is not in the color palette")]
+    UnknownColorKey { key: char },
+
+    #[error("the COLORS footer must follow a blank line")]
+    UnseparatedFooter,
+}
+
+/// A position in a chart file, for error messages. Lines and columns start at 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Location {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    /// The text of the line, without its line ending.
+    pub text: String,
+}
+
+impl Location {
+    /// The offending line with a caret under the offending character, e.g.
+    ///
+    /// ```
+    /// let location = dklib::Location {
+    ///     file: None,
+    ///     line: 3,
+    ///     column: 4,
+    ///     text: "..*x..".to_string(),
+    /// };
+    /// assert_eq!(location.snippet(), "3 | ..*x..\n  |    ^");
+    /// ```
+    pub fn snippet(&self) -> String {
+        let gutter = self.line.to_string();
+        format!(
+            "{} | {}\n{} | {}^",
+            gutter,
+            self.text,
+            " ".repeat(gutter.len()),
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file.display(), self.line, self.column),
+            None => write!(f, "line {}, column {}", self.line, self.column),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,31 +1,78 @@
 use crate::Error;
 use crate::{
-    chart::Chart,
-    units::{Height, Width},
+    chart::{Chart, ReadingDirection},
+    units::{Cols, Height, Rows, Width},
 };
 use fehler::throws;
 use image::{Rgb, RgbImage};
 use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_line_segment_mut};
 use imageproc::rect::Rect;
+#[cfg(feature = "embedded-font")]
+use once_cell::sync::Lazy;
+use rusttype::{point, Font, Scale};
 use std::path::Path;
 
+// Embedded so that charts render the same everywhere. DejaVu Sans covers the geometric shapes
+// and box drawing characters that knitting fonts use for stitch symbols.
+#[cfg(feature = "embedded-font")]
+const FONT_DATA: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+
+// The font for row numbers and stitch symbols, parsed the first time it is needed.
+#[cfg(feature = "embedded-font")]
+#[throws]
+fn chart_font() -> Option<&'static Font<'static>> {
+    static FONT: Lazy<Option<Font<'static>>> = Lazy::new(|| Font::try_from_bytes(FONT_DATA));
+    Some(FONT.as_ref().ok_or(Error::BadFont)?)
+}
+
+// Without the embedded font, the images have no text.
+#[cfg(not(feature = "embedded-font"))]
+#[throws]
+fn chart_font() -> Option<&'static Font<'static>> {
+    None
+}
+
 #[throws]
 pub fn the_thing(filename: impl AsRef<Path>, chart: &Chart) {
+    the_thing_highlighted(filename, chart, &[])?
+}
+
+/// Like `the_thing`, but outlines each (row, col, width) run of stitches in red.
+///
+/// Rows are numbered from 1 at the bottom. The numbers are on the side where each row starts,
+/// which is the right unless the chart is read from left to right.
+#[throws]
+pub fn the_thing_highlighted(
+    filename: impl AsRef<Path>,
+    chart: &Chart,
+    highlights: &[(Rows, Cols, Width)],
+) {
+    render_chart(chart, highlights)?.save(filename.as_ref())?;
+}
+
+// Draw the chart image that `the_thing_highlighted` saves.
+#[throws]
+pub(crate) fn render_chart(chart: &Chart, highlights: &[(Rows, Cols, Width)]) -> RgbImage {
     let cell_size = 15;
     let dot_size = 8;
     let background_color = Rgb([255, 255, 255]);
     let grid_color = Rgb([0, 0, 0]);
+    let number_width = 2 * cell_size;
+    let font = chart_font()?;
 
     let rows = chart.rows();
     let cols = chart.cols();
-    let mut img = RgbImage::new(
-        u32::from(Width::from(cols)) * cell_size,
-        u32::from(Height::from(rows)) * cell_size,
-    );
+    let grid_width = u32::from(Width::from(cols)) * cell_size;
+    let grid_height = u32::from(Height::from(rows)) * cell_size;
+    let (grid_left, number_left) = match chart.reading_direction() {
+        ReadingDirection::RightToLeft => (0, grid_width),
+        ReadingDirection::LeftToRight => (number_width, 0),
+    };
+    let mut img = RgbImage::new(grid_width + number_width, grid_height);
 
     draw_filled_rect_mut(
         &mut img,
-        Rect::at(0, 0).of_size(u32::from(cols) * cell_size, u32::from(rows) * cell_size),
+        Rect::at(0, 0).of_size(grid_width + number_width, grid_height),
         background_color,
     );
 
@@ -33,25 +80,39 @@ pub fn the_thing(filename: impl AsRef<Path>, chart: &Chart) {
         let row_offset = (row * cell_size) as f32;
         draw_line_segment_mut(
             &mut img,
-            (0.0 - 0.5, row_offset - 0.5),
-            ((cols * cell_size) as f32 - 0.5, row_offset - 0.5),
+            (grid_left as f32 - 0.5, row_offset - 0.5),
+            ((grid_left + grid_width) as f32 - 0.5, row_offset - 0.5),
             grid_color,
         );
     }
 
     for col in cols + 1 {
-        let col_offset = (col * cell_size) as f32;
+        let col_offset = (grid_left + col * cell_size) as f32;
         draw_line_segment_mut(
             &mut img,
             (col_offset - 0.5, 0.0 - 0.5),
-            (col_offset - 0.5, (rows * cell_size) as f32 - 0.5),
+            (col_offset - 0.5, grid_height as f32 - 0.5),
             grid_color,
         );
     }
 
+    for row in rows {
+        let number = u32::from(rows) - u32::from(row);
+        if let Some(font) = font {
+            draw_text(
+                &mut img,
+                font,
+                &number.to_string(),
+                (cell_size - 4) as f32,
+                (number_left, row * cell_size, number_width, cell_size),
+                grid_color,
+            );
+        }
+    }
+
     for row in rows {
         for col in cols {
-            let cell_x = col * cell_size;
+            let cell_x = grid_left + col * cell_size;
             let cell_y = row * cell_size;
 
             let stitch = chart.stitch(row, col)?;
@@ -63,7 +124,9 @@ pub fn the_thing(filename: impl AsRef<Path>, chart: &Chart) {
                 )
             }
 
-            if stitch.symbol() == '*' {
+            if stitch.is_empty() {
+                continue;
+            } else if stitch.symbol() == '*' {
                 draw_filled_circle_mut(
                     &mut img,
                     (
@@ -73,9 +136,83 @@ pub fn the_thing(filename: impl AsRef<Path>, chart: &Chart) {
                     dot_size / 2,
                     grid_color,
                 );
+            } else if let Some(font) = font {
+                draw_text(
+                    &mut img,
+                    font,
+                    &stitch.symbol().to_string(),
+                    cell_size as f32,
+                    (cell_x, cell_y, cell_size, cell_size),
+                    grid_color,
+                );
             }
         }
     }
 
-    img.save(filename.as_ref())?;
+    let highlight_color = Rgb([255, 0, 0]);
+    for (row, col, width) in highlights {
+        let left = (grid_left + *col * cell_size) as f32;
+        let top = (*row * cell_size) as f32;
+        let right = (grid_left + (*col + *width) * cell_size) as f32;
+        let bottom = ((*row + 1) * cell_size) as f32;
+        // Draw the outline twice, one pixel apart, to make it stand out from the grid.
+        for inset in &[0.5, 1.5] {
+            let (l, t, r, b) = (left + inset, top + inset, right - inset, bottom - inset);
+            draw_line_segment_mut(&mut img, (l, t), (r, t), highlight_color);
+            draw_line_segment_mut(&mut img, (r, t), (r, b), highlight_color);
+            draw_line_segment_mut(&mut img, (r, b), (l, b), highlight_color);
+            draw_line_segment_mut(&mut img, (l, b), (l, t), highlight_color);
+        }
+    }
+
+    img
+}
+
+// Draw `text` centered in the (x, y, width, height) area, blended into what is already there.
+// Anything that doesn't fit in the area is clipped.
+fn draw_text(
+    img: &mut RgbImage,
+    font: &Font,
+    text: &str,
+    size: f32,
+    area: (u32, u32, u32, u32),
+    color: Rgb<u8>,
+) {
+    let (area_x, area_y, area_width, area_height) =
+        (area.0 as i32, area.1 as i32, area.2 as i32, area.3 as i32);
+    let glyphs: Vec<_> = font
+        .layout(text, Scale::uniform(size), point(0.0, 0.0))
+        .filter_map(|glyph| glyph.pixel_bounding_box().map(|bounds| (glyph, bounds)))
+        .collect();
+    // Nothing to draw, e.g. for whitespace.
+    if glyphs.is_empty() {
+        return;
+    }
+
+    let min_x = glyphs.iter().map(|(_, b)| b.min.x).min().unwrap_or(0);
+    let max_x = glyphs.iter().map(|(_, b)| b.max.x).max().unwrap_or(0);
+    let min_y = glyphs.iter().map(|(_, b)| b.min.y).min().unwrap_or(0);
+    let max_y = glyphs.iter().map(|(_, b)| b.max.y).max().unwrap_or(0);
+    let left = area_x + (area_width - (max_x - min_x)) / 2 - min_x;
+    let top = area_y + (area_height - (max_y - min_y)) / 2 - min_y;
+
+    for (glyph, bounds) in &glyphs {
+        glyph.draw(|x, y, coverage| {
+            let px = left + bounds.min.x + x as i32;
+            let py = top + bounds.min.y + y as i32;
+            let in_area = px >= area_x
+                && py >= area_y
+                && px < area_x + area_width
+                && py < area_y + area_height;
+            if !in_area {
+                return;
+            }
+            let pixel = img.get_pixel_mut(px as u32, py as u32);
+            for (channel, target) in pixel.0.iter_mut().zip(&color.0) {
+                let blended =
+                    f32::from(*channel) * (1.0 - coverage) + f32::from(*target) * coverage;
+                *channel = blended.round() as u8;
+            }
+        });
+    }
 }
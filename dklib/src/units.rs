@@ -37,6 +37,7 @@ macro_rules! unit_binop {
 macro_rules! define_unit {
     ($name:ident) => {
         #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name(u16);
         impl Display for $name {
             fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {